@@ -0,0 +1,185 @@
+use std::fs;
+
+use crate::{
+    camera::Camera,
+    vec3::{Point3, Vec3},
+};
+
+#[derive(Clone, Copy, Debug)]
+pub struct Keyframe {
+    pub time: f64,
+    pub position: Point3,
+    pub target: Point3,
+    pub vfov: f64,
+    pub focus_dist: f64,
+    pub aperture: f64, //defocus angle, or the f-number on cameras with a physical lens
+}
+
+impl Keyframe {
+    fn lerp(a: &Keyframe, b: &Keyframe, t: f64) -> Keyframe {
+        Keyframe {
+            time: a.time + t * (b.time - a.time),
+            position: a.position + t * (b.position - a.position),
+            target: a.target + t * (b.target - a.target),
+            vfov: a.vfov + t * (b.vfov - a.vfov),
+            focus_dist: a.focus_dist + t * (b.focus_dist - a.focus_dist),
+            aperture: a.aperture + t * (b.aperture - a.aperture),
+        }
+    }
+
+    fn scaled(&self, s: f64) -> Keyframe {
+        Keyframe {
+            time: s * self.time,
+            position: s * self.position,
+            target: s * self.target,
+            vfov: s * self.vfov,
+            focus_dist: s * self.focus_dist,
+            aperture: s * self.aperture,
+        }
+    }
+
+    fn sum(a: &Keyframe, b: &Keyframe) -> Keyframe {
+        Keyframe {
+            time: a.time + b.time,
+            position: a.position + b.position,
+            target: a.target + b.target,
+            vfov: a.vfov + b.vfov,
+            focus_dist: a.focus_dist + b.focus_dist,
+            aperture: a.aperture + b.aperture,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Interpolation {
+    Linear,
+    CatmullRom, //passes through every keyframe with smooth tangents
+    Bezier,     //keyframes are the control polygon of one smooth curve over the whole path
+}
+
+// Camera path loaded from a plain text file, one entry per line:
+//   interpolation linear|catmull-rom|bezier
+//   key <time> <px> <py> <pz> <tx> <ty> <tz> <vfov> <focus_dist> <aperture>
+// aperture is the defocus angle, or the f-number when the camera has a physical lens.
+// blank lines and lines starting with # are skipped, keys can be in any order.
+pub struct CameraPath {
+    keyframes: Vec<Keyframe>,
+    interpolation: Interpolation,
+}
+
+impl CameraPath {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+        let mut keyframes: Vec<Keyframe> = Vec::new();
+        let mut interpolation = Interpolation::Linear;
+
+        for (line_no, line) in text.lines().enumerate() {
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            match parts.first() {
+                None => {}
+                Some(p) if p.starts_with('#') => {}
+                Some(&"interpolation") => {
+                    interpolation = match parts.get(1) {
+                        Some(&"linear") => Interpolation::Linear,
+                        Some(&"catmull-rom") => Interpolation::CatmullRom,
+                        Some(&"bezier") => Interpolation::Bezier,
+                        _ => return Err(format!("line {}: unknown interpolation", line_no + 1)),
+                    }
+                }
+                Some(&"key") if parts.len() == 11 => {
+                    let values: Vec<f64> = parts[1..]
+                        .iter()
+                        .map(|v| v.parse::<f64>())
+                        .collect::<Result<_, _>>()
+                        .map_err(|e| format!("line {}: {}", line_no + 1, e))?;
+                    keyframes.push(Keyframe {
+                        time: values[0],
+                        position: Point3::new(values[1], values[2], values[3]),
+                        target: Point3::new(values[4], values[5], values[6]),
+                        vfov: values[7],
+                        focus_dist: values[8],
+                        aperture: values[9],
+                    });
+                }
+                _ => return Err(format!("line {}: can't parse '{}'", line_no + 1, line)),
+            }
+        }
+
+        if keyframes.is_empty() {
+            return Err(format!("{}: no keyframes", path));
+        }
+        keyframes.sort_by(|a, b| a.time.total_cmp(&b.time));
+        Ok(Self {
+            keyframes,
+            interpolation,
+        })
+    }
+
+    pub fn sample(&self, time: f64) -> Keyframe {
+        let keys = &self.keyframes;
+        let first = keys[0];
+        let last = keys[keys.len() - 1];
+        if keys.len() == 1 || time <= first.time {
+            return first;
+        }
+        if time >= last.time {
+            return last;
+        }
+
+        if self.interpolation == Interpolation::Bezier {
+            let t = (time - first.time) / (last.time - first.time);
+            return Self::de_casteljau(keys, t);
+        }
+
+        //segment i runs from keys[i] to keys[i + 1]
+        let i = keys.iter().rposition(|k| k.time <= time).unwrap_or(0);
+        let (k1, k2) = (&keys[i], &keys[i + 1]);
+        let t = (time - k1.time) / (k2.time - k1.time);
+        match self.interpolation {
+            Interpolation::CatmullRom => {
+                let k0 = &keys[i.saturating_sub(1)];
+                let k3 = &keys[(i + 2).min(keys.len() - 1)];
+                Self::catmull_rom(k0, k1, k2, k3, t)
+            }
+            _ => Keyframe::lerp(k1, k2, t),
+        }
+    }
+
+    //uniform Catmull-Rom between k1 and k2
+    fn catmull_rom(k0: &Keyframe, k1: &Keyframe, k2: &Keyframe, k3: &Keyframe, t: f64) -> Keyframe {
+        let t2 = t * t;
+        let t3 = t2 * t;
+        let w0 = -0.5 * t3 + t2 - 0.5 * t;
+        let w1 = 1.5 * t3 - 2.5 * t2 + 1_f64;
+        let w2 = -1.5 * t3 + 2_f64 * t2 + 0.5 * t;
+        let w3 = 0.5 * t3 - 0.5 * t2;
+        let a = Keyframe::sum(&k0.scaled(w0), &k1.scaled(w1));
+        let b = Keyframe::sum(&k2.scaled(w2), &k3.scaled(w3));
+        Keyframe::sum(&a, &b)
+    }
+
+    fn de_casteljau(keys: &[Keyframe], t: f64) -> Keyframe {
+        let mut points: Vec<Keyframe> = keys.to_vec();
+        while points.len() > 1 {
+            points = points
+                .windows(2)
+                .map(|pair| Keyframe::lerp(&pair[0], &pair[1], t))
+                .collect();
+        }
+        points[0]
+    }
+
+    pub fn apply(&self, time: f64, cam: &mut Camera) {
+        let key = self.sample(time);
+        cam.look_at(key.position, key.target, Vec3::new(0_f64, 1_f64, 0_f64));
+        cam.set_vfov(key.vfov);
+        cam.focus_dist = key.focus_dist;
+        //a physical lens ignores defocus_angle, so the f-number is what opens and closes
+        match cam.physical.as_mut() {
+            //f/0 would be an infinitely wide opening
+            Some(lens) if key.aperture > 0_f64 => lens.f_number = key.aperture,
+            Some(_) => {}
+            None => cam.defocus_angle = key.aperture,
+        }
+    }
+}
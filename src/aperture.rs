@@ -0,0 +1,149 @@
+use std::fs;
+use std::sync::Arc;
+
+use crate::{
+    helper::PI,
+    vec3::{sample_unit_disk, Vec3},
+};
+
+// Shape of the lens opening, which is also the shape out of focus highlights take. Every shape
+// is sampled inside the unit square [-1,1]^2 and then scaled by the defocus radius.
+#[derive(Clone)]
+pub enum Aperture {
+    Circle,
+    //regular polygon with its corners on the unit circle, rotation in radians
+    Polygon { blades: u32, rotation: f64 },
+    Mask(Arc<ApertureMask>), //shared so cameras can move to other threads
+}
+
+impl Aperture {
+    pub fn sample(&self, u: (f64, f64)) -> Vec3 {
+        match self {
+            Aperture::Circle => sample_unit_disk(u),
+            Aperture::Polygon { blades, rotation } => sample_polygon(*blades, *rotation, u),
+            Aperture::Mask(mask) => mask.sample(u),
+        }
+    }
+}
+
+fn sample_polygon(blades: u32, rotation: f64, u: (f64, f64)) -> Vec3 {
+    let blades = blades.max(3);
+    //pick one of the equal area triangles fanning out from the center and reuse what's left
+    //of the first dimension inside it
+    let scaled = u.0 * blades as f64;
+    let sector = (scaled as u32).min(blades - 1);
+    let u0 = scaled - sector as f64;
+
+    let corner = |k: u32| {
+        let angle = rotation + 2_f64 * PI * k as f64 / blades as f64;
+        Vec3::new(angle.cos(), angle.sin(), 0_f64)
+    };
+    //uniform point in the triangle (center, a, b)
+    let su = u0.sqrt();
+    su * (1_f64 - u.1) * corner(sector) + (su * u.1) * corner(sector + 1)
+}
+
+// Grayscale aperture mask, brighter pixels let more light through. Loaded from a binary or
+// ascii PGM (P5/P2) or PPM (P6/P3, converted to gray); the image spans the unit square.
+pub struct ApertureMask {
+    width: usize,
+    height: usize,
+    cdf: Vec<f64>, //running sum of pixel weights, normalised to end at 1
+}
+
+impl ApertureMask {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let bytes = fs::read(path).map_err(|e| format!("{}: {}", path, e))?;
+        let (width, height, gray) = parse_pnm(&bytes).map_err(|e| format!("{}: {}", path, e))?;
+
+        let mut cdf = Vec::with_capacity(gray.len());
+        let mut total = 0_f64;
+        for value in &gray {
+            total += value;
+            cdf.push(total);
+        }
+        if total <= 0_f64 {
+            return Err(format!("{}: mask is completely black", path));
+        }
+        cdf.iter_mut().for_each(|c| *c /= total);
+        Ok(Self { width, height, cdf })
+    }
+
+    fn sample(&self, u: (f64, f64)) -> Vec3 {
+        //pick a pixel proportionally to its brightness, then jitter inside it, with the leftover
+        //of the first dimension used for x so both dimensions stay stratified
+        let idx = self
+            .cdf
+            .partition_point(|&c| c <= u.0)
+            .min(self.cdf.len() - 1);
+        let lo = if idx == 0 { 0_f64 } else { self.cdf[idx - 1] };
+        let jitter_x = ((u.0 - lo) / (self.cdf[idx] - lo)).clamp(0_f64, 1_f64);
+        let (px, py) = (idx % self.width, idx / self.width);
+        let x = (px as f64 + jitter_x) / self.width as f64;
+        let y = (py as f64 + u.1) / self.height as f64;
+        //image rows go down, aperture y goes up
+        Vec3::new(2_f64 * x - 1_f64, 1_f64 - 2_f64 * y, 0_f64)
+    }
+}
+
+//returns width, height and gray values in [0, 1]
+fn parse_pnm(bytes: &[u8]) -> Result<(usize, usize, Vec<f64>), String> {
+    //header is magic, width, height, maxval separated by whitespace, with # comments
+    let mut pos = 0;
+    let mut fields: Vec<String> = Vec::new();
+    while fields.len() < 4 && pos < bytes.len() {
+        match bytes[pos] {
+            b'#' => {
+                while pos < bytes.len() && bytes[pos] != b'\n' {
+                    pos += 1;
+                }
+            }
+            b if b.is_ascii_whitespace() => pos += 1,
+            _ => {
+                let start = pos;
+                while pos < bytes.len() && !bytes[pos].is_ascii_whitespace() {
+                    pos += 1;
+                }
+                fields.push(String::from_utf8_lossy(&bytes[start..pos]).to_string());
+            }
+        }
+    }
+    if fields.len() < 4 {
+        return Err("truncated header".to_string());
+    }
+    let channels = match fields[0].as_str() {
+        "P2" | "P5" => 1,
+        "P3" | "P6" => 3,
+        other => return Err(format!("unsupported format {}", other)),
+    };
+    let parse = |s: &str| s.parse::<usize>().map_err(|e| e.to_string());
+    let (width, height, maxval) = (parse(&fields[1])?, parse(&fields[2])?, parse(&fields[3])?);
+    if width == 0 || height == 0 || maxval == 0 || maxval > 255 {
+        return Err("unsupported image size or bit depth".to_string());
+    }
+
+    let count = width * height * channels;
+    let samples: Vec<f64> = match fields[0].as_str() {
+        "P5" | "P6" => {
+            //exactly one whitespace byte separates the header from the binary data
+            let data = bytes
+                .get(pos + 1..pos + 1 + count)
+                .ok_or("truncated data")?;
+            data.iter().map(|&b| b as f64).collect()
+        }
+        _ => String::from_utf8_lossy(&bytes[pos..])
+            .split_whitespace()
+            .take(count)
+            .map(|v| v.parse::<f64>().map_err(|e| e.to_string()))
+            .collect::<Result<_, _>>()?,
+    };
+    if samples.len() != count {
+        return Err("truncated data".to_string());
+    }
+
+    let gray = samples
+        .chunks(channels)
+        .map(|px| px.iter().sum::<f64>() / (channels * maxval) as f64)
+        .collect();
+    Ok((width, height, gray))
+}
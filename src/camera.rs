@@ -1,29 +1,230 @@
-use crate::helper::INFINITY;
+use std::sync::atomic::{AtomicBool, AtomicI32, AtomicU32, Ordering};
+use std::sync::Arc;
+use std::thread;
+
+use crate::helper::{INFINITY, PI};
 use crate::{
-    color::{write_color, Color},
-    helper::{deg_to_rad, random_f64},
+    aperture::Aperture,
+    color::{luminance, write_color, Color},
+    filter::{Filter, FilterKind},
+    helper::deg_to_rad,
     interval::Interval,
+    quat::Quat,
     ray::{HitRecord, Hittable, Ray},
-    vec3::{cross, random_in_unit_disk, unit_vector, Point3, Vec3},
+    sampler::{make_sampler, Sampler, SamplerKind},
+    vec3::{cross, dot, unit_vector, Point3, Vec3},
 };
 
+//intersection tests per path that the traversal cost view shows as fully red
+const MAX_TRAVERSAL_COST: f64 = 65536_f64;
+//rows in a band of a render, see render_view
+const ROWS_PER_BAND: i32 = 8;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Projection {
+    Perspective,     //thin lens, the default
+    Orthographic,    //parallel rays, viewport sized like the perspective one at focus_dist
+    Fisheye,         //equidistant, fisheye_fov across the vertical diameter of the image circle
+    Equirectangular, //full 360 x 180 latitude/longitude panorama
+}
+
+impl Projection {
+    pub fn next(self) -> Self {
+        match self {
+            Projection::Perspective => Projection::Orthographic,
+            Projection::Orthographic => Projection::Fisheye,
+            Projection::Fisheye => Projection::Equirectangular,
+            Projection::Equirectangular => Projection::Perspective,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StereoMode {
+    Mono,
+    SideBySide,      //left | right, each eye the full mono view squeezed to half width
+    TopBottom,       //left over right, each eye the full mono view squeezed to half height
+    Anaglyph,        //red from the left eye, green/blue from the right
+    OmniDirectional, //omnidirectional stereo equirectangular, left over right
+}
+
+impl StereoMode {
+    pub fn next(self) -> Self {
+        match self {
+            StereoMode::Mono => StereoMode::SideBySide,
+            StereoMode::SideBySide => StereoMode::TopBottom,
+            StereoMode::TopBottom => StereoMode::Anaglyph,
+            StereoMode::Anaglyph => StereoMode::OmniDirectional,
+            StereoMode::OmniDirectional => StereoMode::Mono,
+        }
+    }
+}
+
+//what ray_color is swapped for, to see what the renderer sees instead of the lit image
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DebugView {
+    Off,
+    Normals,       //normal facing the ray, xyz as rgb
+    Depth,         //distance to the first hit, bright when near, black for the sky
+    Albedo,        //material color of the first hit, unlit
+    UvChecker,     //checkerboard in the surface coordinates of the first hit
+    ObjectIds,     //a random color per top level object
+    Bounces,       //heatmap of how many rays the path took, up to max_depth
+    TraversalCost, //heatmap of the intersection tests the whole path took, log scaled
+}
+
+impl DebugView {
+    pub fn next(self) -> Self {
+        match self {
+            DebugView::Off => DebugView::Normals,
+            DebugView::Normals => DebugView::Depth,
+            DebugView::Depth => DebugView::Albedo,
+            DebugView::Albedo => DebugView::UvChecker,
+            DebugView::UvChecker => DebugView::ObjectIds,
+            DebugView::ObjectIds => DebugView::Bounces,
+            DebugView::Bounces => DebugView::TraversalCost,
+            DebugView::TraversalCost => DebugView::Off,
+        }
+    }
+}
+
+//rays and intersection tests a render took, ray_color adds to it
+#[derive(Default)]
+struct TraceStats {
+    rays: u64,
+    tests: u64,
+}
+
+//what one band of rows added to the image. The filter reaches past the band, so the sums start
+//at sums_row rather than first_row
+struct Band {
+    first_row: i32,
+    sums_row: i32,
+    color_sums: Vec<Color>,
+    weight_sums: Vec<f64>,
+    sample_counts: Vec<i32>, //the band's own rows
+    stats: TraceStats,
+}
+
+// Photographic camera settings. When set on a Camera they replace vfov and defocus_angle and
+// add an exposure multiplier. Scene units are taken to be meters, and scene radiance is
+// calibrated so the "sunny 16" settings (f/16, 1/100s, ISO 100) leave the image unchanged.
+#[derive(Clone, Copy, Debug)]
+pub struct PhysicalLens {
+    pub focal_length_mm: f64,
+    pub f_number: f64,
+    pub sensor_height_mm: f64, //24mm for full frame
+    pub shutter_s: f64,
+    pub iso: f64,
+}
+
+impl PhysicalLens {
+    pub fn new(focal_length_mm: f64, f_number: f64, shutter_s: f64, iso: f64) -> Self {
+        Self {
+            focal_length_mm,
+            f_number,
+            sensor_height_mm: 24_f64,
+            shutter_s,
+            iso,
+        }
+    }
+
+    pub fn vfov(&self) -> f64 {
+        2_f64 * (self.sensor_height_mm / (2_f64 * self.focal_length_mm)).atan() * 180_f64 / PI
+    }
+
+    //radius of the entrance pupil in scene units (meters)
+    pub fn aperture_radius(&self) -> f64 {
+        self.focal_length_mm / (2_f64 * self.f_number) / 1000_f64
+    }
+
+    //light reaching the sensor scales with t * ISO / N^2, relative to sunny 16
+    pub fn exposure(&self) -> f64 {
+        let sunny_16 = 0.01 * 100_f64 / (16_f64 * 16_f64);
+        (self.shutter_s * self.iso / (self.f_number * self.f_number)) / sunny_16
+    }
+
+    //one full stop on the aperture ring, positive stops down (smaller aperture)
+    pub fn step_f_stop(&mut self, stops: f64) {
+        self.f_number *= std::f64::consts::SQRT_2.powf(stops);
+    }
+}
+
+// Shared between a render running on another thread and whoever is waiting on it: how far
+// along it is, and a flag asking it to stop early.
+#[derive(Default)]
+pub struct RenderControl {
+    progress: AtomicU32, //per mille
+    cancelled: AtomicBool,
+}
+
+impl RenderControl {
+    pub fn progress(&self) -> f64 {
+        self.progress.load(Ordering::Relaxed) as f64 / 1000_f64
+    }
+
+    fn set_progress(&self, done: f64) {
+        self.progress
+            .store((done * 1000_f64) as u32, Ordering::Relaxed);
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+//what's under a pixel, see Camera::pick
+#[derive(Clone, Copy, Debug)]
+pub struct Pick {
+    pub object_id: usize,
+    pub distance: f64, //from the camera to the hit point
+    pub depth: f64,    //along the view direction, i.e. what focus_dist measures
+}
+
+#[derive(Clone)]
 pub struct Camera {
     aspect_ratio: f64,
     img_width: i32,
     img_height: i32,
+    pixel_aspect: f64, //width over height of one pixel, only squeezed stereo eyes aren't square
     samples_per_pixel: i32,
+    pub max_samples_per_pixel: i32, //adaptive sampling keeps going up to this many samples
+    pub adaptive_threshold: f64,    //relative std error a pixel has to get under to stop early
+    sample_counts: Vec<i32>,        //samples each pixel took in the last render
+    rays_traced: u64,               //camera and bounce rays in the last render
+    pub control: Option<Arc<RenderControl>>, //set when another thread watches this render
+    progress_span: (f64, f64),      //start and length of the progress this view covers
     max_depth: i32,
-    pixels_sample_scale: f64,
+    pub rr_min_depth: i32, //bounces before russian roulette may terminate a path
     center: Point3,
     pixel00_loc: Point3,
     pixel_delta_u: Vec3,
     pixel_delta_v: Vec3,
     vfov: f64,            //vertical angle fov
     pub lookfrom: Point3, //point angle looking from
-    pub lookat: Point3,   // point angle looking at
-    pub vup: Vec3,        // camera relative up direction
+    lookat: Point3,       // point angle looking at, derived from orientation
+    vup: Vec3,            // camera relative up direction, derived from orientation
+    orientation: Quat,    //rotation from camera local axes to world, the source of truth
+    look_dist: f64,       //how far in front of lookfrom lookat sits
     pub defocus_angle: f64,
     pub focus_dist: f64,
+    pub physical: Option<PhysicalLens>, //when set, vfov/defocus_angle/exposure come from this
+    pub aperture: Aperture,             //shape of the lens opening (and of the bokeh)
+    exposure: f64,
+    pub seed: u64, //same seed + same scene/camera = bit-identical image
+    pub sampler: SamplerKind,
+    pub filter: Filter,
+    pub projection: Projection,
+    pub fisheye_fov: f64,
+    pub stereo: StereoMode,
+    pub debug_view: DebugView,
+    pub interocular: f64,      //distance between the eyes
+    pub convergence_dist: f64, //distance of the zero parallax plane
+    eye: f64,                  //-0.5 left eye, 0.5 right eye, 0 mono
     u: Vec3,
     v: Vec3,
     w: Vec3,
@@ -43,9 +244,16 @@ impl Camera {
             aspect_ratio,
             img_width,
             img_height: 0,
+            pixel_aspect: 1_f64,
             samples_per_pixel,
-            pixels_sample_scale: 0_f64,
+            max_samples_per_pixel: samples_per_pixel,
+            adaptive_threshold: 0_f64,
+            sample_counts: Vec::new(),
+            rays_traced: 0,
+            control: None,
+            progress_span: (0_f64, 1_f64),
             max_depth,
+            rr_min_depth: 3,
             center: Point3::new_empty(),
             pixel00_loc: Point3::new_empty(),
             pixel_delta_u: Vec3::new_empty(),
@@ -53,62 +261,476 @@ impl Camera {
             lookfrom: Point3::new_empty(),
             lookat: Point3::new(0_f64, 0_f64, -1_f64),
             vup: Point3::new(0_f64, 1_f64, 0_f64),
+            orientation: Quat::identity(),
+            look_dist: 1_f64,
             u: Point3::new_empty(),
             v: Point3::new_empty(),
             w: Point3::new_empty(),
             vfov,
             defocus_angle: 0_f64,
             focus_dist: 0_f64,
+            physical: None,
+            aperture: Aperture::Circle,
+            exposure: 1_f64,
+            seed: 0,
+            sampler: SamplerKind::Independent,
+            filter: Filter::new(FilterKind::Box, 0.5),
+            projection: Projection::Perspective,
+            fisheye_fov: 180_f64,
+            stereo: StereoMode::Mono,
+            debug_view: DebugView::Off,
+            interocular: 0.065,
+            convergence_dist: 10_f64,
+            eye: 0_f64,
             defocus_disk_u: Vec3::new_empty(),
             defocus_disk_v: Vec3::new_empty(),
         }
     }
 
     pub fn render(&mut self, world: &dyn Hittable) -> Vec<u8> {
+        let pixels = self.render_linear(world);
+        let mut image = vec![0u8; pixels.len() * 3]; //rgb buffer
+        for (i, pixel) in pixels.iter().enumerate() {
+            let rgb = write_color(pixel);
+            image[i * 3] = rgb[0] as u8;
+            image[i * 3 + 1] = rgb[1] as u8;
+            image[i * 3 + 2] = rgb[2] as u8;
+        }
+        image
+    }
+
+    //exposed but otherwise unprocessed radiance, one color per pixel
+    pub fn render_linear(&mut self, world: &dyn Hittable) -> Vec<Color> {
+        match self.stereo {
+            StereoMode::Mono => self.render_view(world),
+            _ => self.render_stereo(world),
+        }
+    }
+
+    fn eye_camera(&self, eye: f64, aspect_ratio: f64) -> Camera {
+        let mut eye_cam = self.clone();
+        eye_cam.stereo = StereoMode::Mono;
+        eye_cam.eye = eye;
+        eye_cam.aspect_ratio = aspect_ratio;
+        if self.stereo == StereoMode::OmniDirectional {
+            eye_cam.projection = Projection::Equirectangular;
+        }
+        eye_cam
+    }
+
+    //renders both eyes and packs them into one image the size of the mono one
+    fn render_stereo(&mut self, world: &dyn Hittable) -> Vec<Color> {
         self.init();
-        let mut image = vec![0u8; (self.img_width * self.img_height * 3) as usize]; //rgb buffer
+        let (width, height) = (self.img_width, self.img_height);
+        let eye_aspect = match self.stereo {
+            StereoMode::SideBySide => self.aspect_ratio / 2_f64,
+            StereoMode::TopBottom | StereoMode::OmniDirectional => self.aspect_ratio * 2_f64,
+            _ => self.aspect_ratio,
+        };
+        let mut left_cam = self.eye_camera(-0.5, eye_aspect);
+        let mut right_cam = self.eye_camera(0.5, eye_aspect);
+        if self.stereo == StereoMode::SideBySide {
+            left_cam.img_width = width / 2;
+            right_cam.img_width = width / 2;
+        }
+        //half the pixels but the mono framing, so every eye pixel covers two pixels' worth of view
+        if matches!(self.stereo, StereoMode::SideBySide | StereoMode::TopBottom) {
+            left_cam.pixel_aspect = self.aspect_ratio / eye_aspect;
+            right_cam.pixel_aspect = self.aspect_ratio / eye_aspect;
+        }
+        //each eye is half of the overall progress
+        let (start, span) = self.progress_span;
+        left_cam.progress_span = (start, span / 2_f64);
+        right_cam.progress_span = (start + span / 2_f64, span / 2_f64);
+        let left = left_cam.render_view(world);
+        let right = right_cam.render_view(world);
+        let (eye_w, eye_h) = (left_cam.img_width, left_cam.img_height);
 
-        for y in 0..self.img_height {
+        let mut image = vec![Color::new_empty(); (width * height) as usize];
+        let mut sample_counts = vec![0; (width * height) as usize];
+        match self.stereo {
+            StereoMode::Anaglyph => {
+                for i in 0..(width * height) as usize {
+                    image[i] = Color::new(left[i].x(), right[i].y(), right[i].z());
+                }
+                sample_counts = left_cam.sample_counts;
+            }
+            StereoMode::SideBySide => {
+                blit(&mut image, width, &left, eye_w, eye_h, 0, 0, 1);
+                blit(&mut image, width, &right, eye_w, eye_h, eye_w, 0, 1);
+                let counts = (&left_cam.sample_counts, &right_cam.sample_counts);
+                blit(&mut sample_counts, width, counts.0, eye_w, eye_h, 0, 0, 1);
+                blit(
+                    &mut sample_counts,
+                    width,
+                    counts.1,
+                    eye_w,
+                    eye_h,
+                    eye_w,
+                    0,
+                    1,
+                );
+            }
+            _ => {
+                blit(&mut image, width, &left, eye_w, eye_h, 0, 0, 1);
+                blit(&mut image, width, &right, eye_w, eye_h, 0, eye_h, 1);
+                let counts = (&left_cam.sample_counts, &right_cam.sample_counts);
+                blit(&mut sample_counts, width, counts.0, eye_w, eye_h, 0, 0, 1);
+                blit(
+                    &mut sample_counts,
+                    width,
+                    counts.1,
+                    eye_w,
+                    eye_h,
+                    0,
+                    eye_h,
+                    1,
+                );
+            }
+        }
+        self.sample_counts = sample_counts;
+        self.rays_traced = left_cam.rays_traced + right_cam.rays_traced;
+        image
+    }
+
+    //rows are split into bands that threads take in turn. Every band sums its samples apart
+    //and the bands are added up in order afterwards, so the image doesn't depend on how many
+    //threads there are or which one got which band
+    fn render_view(&mut self, world: &dyn Hittable) -> Vec<Color> {
+        self.init();
+        let (width, height) = (self.img_width, self.img_height);
+        //debug views show their colors as they are
+        let exposure = match self.debug_view {
+            DebugView::Off => self.exposure,
+            _ => 1_f64,
+        };
+
+        let band_count = (height + ROWS_PER_BAND - 1) / ROWS_PER_BAND;
+        let next_band = AtomicI32::new(0);
+        let rows_done = AtomicI32::new(0);
+        let threads = thread::available_parallelism().map_or(1, |n| n.get());
+        let cam = &*self;
+        let mut bands: Vec<Band> = thread::scope(|scope| {
+            let workers: Vec<_> = (0..threads)
+                .map(|_| {
+                    scope.spawn(|| {
+                        let mut done = Vec::new();
+                        loop {
+                            let band = next_band.fetch_add(1, Ordering::Relaxed);
+                            if band >= band_count || cam.is_cancelled() {
+                                return done;
+                            }
+                            done.push(cam.render_band(world, band * ROWS_PER_BAND, &rows_done));
+                        }
+                    })
+                })
+                .collect();
+            workers
+                .into_iter()
+                .flat_map(|worker| worker.join().expect("render thread panicked"))
+                .collect()
+        });
+        bands.sort_by_key(|band| band.first_row);
+
+        //weighted sample sums and filter weight sums for every pixel
+        let mut color_sums = vec![Color::new_empty(); (width * height) as usize];
+        let mut weight_sums = vec![0_f64; (width * height) as usize];
+        let mut sample_counts = vec![0; (width * height) as usize];
+        let mut stats = TraceStats::default();
+        for band in bands {
+            let sums_start = (band.sums_row * width) as usize;
+            for (i, (color, weight)) in band.color_sums.iter().zip(&band.weight_sums).enumerate() {
+                color_sums[sums_start + i] += *color;
+                weight_sums[sums_start + i] += weight;
+            }
+            let start = (band.first_row * width) as usize;
+            sample_counts[start..start + band.sample_counts.len()]
+                .copy_from_slice(&band.sample_counts);
+            stats.rays += band.stats.rays;
+            stats.tests += band.stats.tests;
+        }
+
+        let image = color_sums
+            .iter()
+            .zip(&weight_sums)
+            .map(|(color_sum, weight_sum)| match weight_sum.abs() {
+                w if w < 1e-8 => Color::new_empty(),
+                _ => (exposure / weight_sum) * *color_sum,
+            })
+            .collect();
+        self.sample_counts = sample_counts;
+        self.rays_traced = stats.rays;
+        image
+    }
+
+    //renders the rows of the band starting at first_row. Whatever got rendered before a cancel
+    //comes back, the caller knows to throw it away
+    fn render_band(&self, world: &dyn Hittable, first_row: i32, rows_done: &AtomicI32) -> Band {
+        let last_row = (first_row + ROWS_PER_BAND).min(self.img_height);
+        //the filter spreads samples onto the rows around the band too
+        let reach = self.filter.radius.ceil() as i32;
+        let sums_row = (first_row - reach).max(0);
+        let sums_len =
+            (((last_row + reach).min(self.img_height) - sums_row) * self.img_width) as usize;
+        let mut band = Band {
+            first_row,
+            sums_row,
+            color_sums: vec![Color::new_empty(); sums_len],
+            weight_sums: vec![0_f64; sums_len],
+            sample_counts: Vec::with_capacity(((last_row - first_row) * self.img_width) as usize),
+            stats: TraceStats::default(),
+        };
+        //patterns cover every sample adaptive sampling may take, not just the base ones
+        let planned = self.samples_per_pixel.max(self.max_samples_per_pixel);
+        let mut sampler = make_sampler(self.sampler, self.seed, planned);
+
+        for y in first_row..last_row {
             for x in 0..self.img_width {
-                let mut pixel_color = Color::new_empty();
-                for _ in 0..self.samples_per_pixel {
-                    let ray = self.get_ray(x, y);
-                    pixel_color += self.ray_color(&ray, world, self.max_depth);
+                let mut lum_sum = 0_f64;
+                let mut lum_sq_sum = 0_f64;
+                let mut sample = 0;
+                //always take the base samples, then keep going while the pixel is still noisy
+                while sample < self.samples_per_pixel
+                    || (sample < self.max_samples_per_pixel
+                        && self.pixel_error(lum_sum, lum_sq_sum, sample) > self.adaptive_threshold)
+                {
+                    //samples are keyed on (seed, pixel, sample index) rather than render order
+                    sampler.start_pixel_sample(x, y, sample);
+                    let offset = self.sample_square(sampler.as_mut());
+                    let sample_color = match self.get_ray(x, y, &offset, Some(sampler.as_mut())) {
+                        Some(ray) => self.shade(&ray, world, sampler.as_mut(), &mut band.stats),
+                        None => Color::new_empty(),
+                    };
+                    let lum = luminance(&sample_color);
+                    lum_sum += lum;
+                    lum_sq_sum += lum * lum;
+                    self.splat(&mut band, x, y, &offset, sample_color);
+                    sample += 1;
                 }
-                let resultant_color = self.pixels_sample_scale * pixel_color;
-                let rgb = write_color(&resultant_color);
-                let offset = ((y * self.img_width + x) * 3) as usize;
+                band.sample_counts.push(sample);
+            }
+            if let Some(control) = &self.control {
+                let (start, span) = self.progress_span;
+                let done = rows_done.fetch_add(1, Ordering::Relaxed) + 1;
+                control.set_progress(start + span * done as f64 / self.img_height as f64);
+                if control.is_cancelled() {
+                    break;
+                }
+            }
+        }
+        band
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.control
+            .as_ref()
+            .is_some_and(|control| control.is_cancelled())
+    }
+
+    //color of one camera sample, the lit path or whatever debug_view asks for
+    fn shade(
+        &self,
+        ray: &Ray,
+        world: &dyn Hittable,
+        sampler: &mut dyn Sampler,
+        stats: &mut TraceStats,
+    ) -> Color {
+        let first_hit = || {
+            let mut hit_rec: HitRecord = HitRecord::new_empty();
+            match world.hit(ray, Interval::new(0.001, INFINITY), &mut hit_rec) {
+                true => Some(hit_rec),
+                false => None,
+            }
+        };
+        //debug colors are meant as displayed, squaring undoes write_color's gamma
+        let display = |c: Color| c * c;
+        match self.debug_view {
+            DebugView::Off => self.ray_color(ray, world, sampler, stats),
+            DebugView::Normals => display(first_hit().map_or(Color::new_empty(), |hit| {
+                0.5 * (hit.normal + Color::new(1_f64, 1_f64, 1_f64))
+            })),
+            DebugView::Depth => display(first_hit().map_or(Color::new_empty(), |hit| {
+                let distance = (hit.p - ray.origin()).get_len();
+                let near = self.focus_dist / (self.focus_dist + distance);
+                Color::new(near, near, near)
+            })),
+            DebugView::Albedo => first_hit()
+                .and_then(|hit| hit.mat.map(|mat| mat.albedo()))
+                .unwrap_or(Color::new_empty()),
+            DebugView::UvChecker => display(first_hit().map_or(Color::new_empty(), |hit| {
+                let cell = (hit.u * 16_f64).floor() as i64 + (hit.v * 8_f64).floor() as i64;
+                match cell % 2 == 0 {
+                    true => Color::new(0.9, 0.9, 0.9),
+                    false => Color::new(0.2, 0.2, 0.2),
+                }
+            })),
+            DebugView::ObjectIds => display(
+                first_hit()
+                    .and_then(|hit| hit.object_id)
+                    .map_or(Color::new_empty(), id_color),
+            ),
+            DebugView::Bounces => {
+                let rays_before = stats.rays;
+                self.ray_color(ray, world, sampler, stats);
+                let rays = (stats.rays - rays_before) as f64;
+                display(heat(rays / self.max_depth.max(1) as f64))
+            }
+            DebugView::TraversalCost => {
+                let tests_before = stats.tests;
+                self.ray_color(ray, world, sampler, stats);
+                let tests = (stats.tests - tests_before) as f64;
+                display(heat(
+                    (1_f64 + tests).ln() / (1_f64 + MAX_TRAVERSAL_COST).ln(),
+                ))
+            }
+        }
+    }
 
-                image[offset] = rgb[0] as u8;
-                image[offset + 1] = rgb[1] as u8;
-                image[offset + 2] = rgb[2] as u8;
+    // Renders the six 90 degree faces around lookfrom into one 6 x 1 strip (each face
+    // face_size square) in the usual +X, -X, +Y, -Y, +Z, -Z order. Side faces are upright with
+    // +Y up, the +Y/-Y faces have -Z/+Z at the top of the image.
+    pub fn render_cubemap(&self, world: &dyn Hittable, face_size: i32) -> Vec<u8> {
+        let faces = [
+            (Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0)),
+            (Vec3::new(-1.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0)),
+            (Vec3::new(0.0, 1.0, 0.0), Vec3::new(0.0, 0.0, -1.0)),
+            (Vec3::new(0.0, -1.0, 0.0), Vec3::new(0.0, 0.0, 1.0)),
+            (Vec3::new(0.0, 0.0, 1.0), Vec3::new(0.0, 1.0, 0.0)),
+            (Vec3::new(0.0, 0.0, -1.0), Vec3::new(0.0, 1.0, 0.0)),
+        ];
+        let row_len = (face_size * 3) as usize;
+        let mut strip = vec![0u8; row_len * faces.len() * face_size as usize];
+
+        for (i, (forward, up)) in faces.iter().enumerate() {
+            let mut face_cam = self.clone();
+            face_cam.projection = Projection::Perspective;
+            face_cam.stereo = StereoMode::Mono;
+            face_cam.aspect_ratio = 1_f64;
+            face_cam.img_width = face_size;
+            face_cam.vfov = 90_f64;
+            face_cam.defocus_angle = 0_f64;
+            face_cam.physical = None; //skybox faces are plain pinhole captures of scene radiance
+            face_cam.lookat = self.lookfrom + *forward;
+            face_cam.vup = *up;
+            let face = face_cam.render(world);
+
+            for row in 0..face_size as usize {
+                let dst = (row * faces.len() + i) * row_len;
+                strip[dst..dst + row_len]
+                    .copy_from_slice(&face[row * row_len..(row + 1) * row_len]);
+            }
+        }
+        strip
+    }
+
+    //add a sample taken at offset from pixel (x, y)'s center into every pixel the filter reaches
+    fn splat(&self, band: &mut Band, x: i32, y: i32, offset: &Vec3, sample_color: Color) {
+        let reach = self.filter.radius.ceil() as i32;
+        for py in (y - reach).max(0)..=(y + reach).min(self.img_height - 1) {
+            for px in (x - reach).max(0)..=(x + reach).min(self.img_width - 1) {
+                let weight = self
+                    .filter
+                    .evaluate((px - x) as f64 - offset.x(), (py - y) as f64 - offset.y());
+                if weight != 0_f64 {
+                    let i = ((py - band.sums_row) * self.img_width + px) as usize;
+                    band.color_sums[i] += weight * sample_color;
+                    band.weight_sums[i] += weight;
+                }
             }
         }
+    }
+
+    //estimated relative error of a pixel's mean luminance after n samples
+    fn pixel_error(&self, lum_sum: f64, lum_sq_sum: f64, n: i32) -> f64 {
+        if n < 2 {
+            return INFINITY;
+        }
+        let n = n as f64;
+        let mean = lum_sum / n;
+        let variance = ((lum_sq_sum / n - mean * mean) * n / (n - 1_f64)).max(0_f64);
+        (variance / n).sqrt() / mean.max(1e-3)
+    }
+
+    //rgb buffer showing how many samples each pixel of the last render took,
+    //blue at samples_per_pixel up through green to red at max_samples_per_pixel
+    pub fn sample_heatmap(&self) -> Vec<u8> {
+        let range = (self.max_samples_per_pixel - self.samples_per_pixel).max(1) as f64;
+        let mut image = vec![0u8; self.sample_counts.len() * 3];
+        for (i, &count) in self.sample_counts.iter().enumerate() {
+            let heat = heat((count - self.samples_per_pixel) as f64 / range);
+            image[i * 3] = (255_f64 * heat.x()) as u8;
+            image[i * 3 + 1] = (255_f64 * heat.y()) as u8;
+            image[i * 3 + 2] = (255_f64 * heat.z()) as u8;
+        }
         image
     }
 
+    //(rays traced, samples taken) in the last render
+    pub fn render_stats(&self) -> (u64, u64) {
+        let samples = self.sample_counts.iter().map(|&n| n as u64).sum();
+        (self.rays_traced, samples)
+    }
+
+    pub fn samples_per_pixel(&self) -> i32 {
+        self.samples_per_pixel
+    }
+
+    pub fn set_samples_per_pixel(&mut self, samples_per_pixel: i32) {
+        self.samples_per_pixel = samples_per_pixel;
+    }
+
+    //the height follows from the aspect ratio
+    pub fn set_width(&mut self, img_width: i32) {
+        self.img_width = img_width;
+    }
+
+    pub fn set_aspect_ratio(&mut self, aspect_ratio: f64) {
+        self.aspect_ratio = aspect_ratio;
+    }
+
+    //(width, height) of the images render produces
+    pub fn dimensions(&self) -> (i32, i32) {
+        let img_height = (self.img_width as f64 / self.aspect_ratio) as i32;
+        let img_height = if img_height < 1 { 1 } else { img_height };
+        (self.img_width, img_height)
+    }
+
+    pub fn set_vfov(&mut self, vfov: f64) {
+        self.vfov = vfov;
+    }
+
+    //the vertical fov renders actually use, which a physical lens overrides
+    pub fn vfov(&self) -> f64 {
+        self.physical.map_or(self.vfov, |lens| lens.vfov())
+    }
+
     fn init(&mut self) {
-        self.img_height = (self.img_width as f64 / self.aspect_ratio) as i32;
-        self.img_height = if self.img_height < 1 {
-            1
-        } else {
-            self.img_height
-        };
+        self.img_height = self.dimensions().1;
 
-        self.pixels_sample_scale = 1.0 / self.samples_per_pixel as f64;
-        self.center = self.lookfrom;
+        //a physical lens, if set, decides the fov, the aperture and the exposure
+        let vfov = self.vfov();
+        let defocus_radius: f64 = match self.physical {
+            Some(lens) => lens.aperture_radius(),
+            None => self.focus_dist * (deg_to_rad(self.defocus_angle / 2_f64).tan()),
+        };
+        self.exposure = self.physical.map_or(1_f64, |lens| lens.exposure());
 
         //determine viewport dimensions
-        let theta = deg_to_rad(self.vfov);
+        let theta = deg_to_rad(vfov);
         let h = (theta / 2.0).tan();
         let viewport_height = 2.0 * h * self.focus_dist;
-        let viewport_width = viewport_height * (self.img_width as f64 / self.img_height as f64);
+        let viewport_width =
+            viewport_height * (self.img_width as f64 * self.pixel_aspect / self.img_height as f64);
 
         //calculate our unit vectors u, v, w for the camera coordinate frame
         self.w = unit_vector(&(self.lookfrom - self.lookat));
         self.u = unit_vector(&cross(&self.vup, &self.w));
         self.v = cross(&self.w, &self.u);
 
+        //each eye sits half the interocular distance off to the side
+        let eye_offset = self.eye * self.interocular;
+        self.center = self.lookfrom + eye_offset * self.u;
+
         //calculate vectors across horizontal and vertical viewpoint edges
         let viewport_u = viewport_width * self.u;
         let viewport_v = viewport_height * (-self.v);
@@ -118,146 +740,334 @@ impl Camera {
         self.pixel_delta_v = viewport_v / self.img_height.into();
 
         //calculate location of upper left pixel
-        let viewport_upper_left =
-            self.center - (self.focus_dist * self.w) - viewport_u / 2_f64 - viewport_v / 2_f64;
+        //off-axis stereo: shift the window back toward the middle so both eyes' frustums meet
+        //on the same rectangle at convergence_dist instead of toeing the eyes in
+        let frustum_shift = -eye_offset * self.focus_dist / self.convergence_dist;
+        let viewport_upper_left = self.center - (self.focus_dist * self.w) + frustum_shift * self.u
+            - viewport_u / 2_f64
+            - viewport_v / 2_f64;
         self.pixel00_loc =
             viewport_upper_left + 0.5_f64 * (self.pixel_delta_u + self.pixel_delta_v);
 
         //calculate camera defocus disk basis vectors
-        let defocus_radius: f64 = self.focus_dist * (deg_to_rad(self.defocus_angle / 2_f64).tan());
         self.defocus_disk_u = defocus_radius * self.u;
-        self.defocus_disk_v = defocus_radius * self.u;
+        self.defocus_disk_v = defocus_radius * self.v;
     }
 
-    fn ray_color(&self, ray: &Ray, world: &dyn Hittable, depth: i32) -> Color {
-        if depth <= 0 {
-            return Color::new_empty();
-        }
+    //iterative path tracer, throughput is the product of all attenuations along the path so far
+    //stats counts every ray cast into the world and the intersection tests they took
+    fn ray_color(
+        &self,
+        ray: &Ray,
+        world: &dyn Hittable,
+        sampler: &mut dyn Sampler,
+        stats: &mut TraceStats,
+    ) -> Color {
+        let mut ray = Ray::new(ray.origin(), ray.dir());
+        let mut throughput = Color::new(1_f64, 1_f64, 1_f64);
+
+        for bounce in 0..self.max_depth {
+            stats.rays += 1;
+            let mut hit_rec: HitRecord = HitRecord::new_empty();
+            let ray_t = Interval::new(0.001, INFINITY);
+            if !world.hit_counted(&ray, ray_t, &mut hit_rec, &mut stats.tests) {
+                let unit_dir: Vec3 = unit_vector(&ray.dir());
+                let a = 0.5_f64 * (unit_dir.y() + 1_f64);
+                let sky = (1_f64 - a) * Color::new(1_f64, 1_f64, 1_f64)
+                    + a * Color::new(0.5_f64, 0.7_f64, 1_f64);
+                return throughput * sky;
+            }
 
-        let mut hit_rec: HitRecord = HitRecord::new_empty();
-        if world.hit(ray, Interval::new(0.001, INFINITY), &mut hit_rec) {
             let mut scattered_ray: Ray = Ray::new_empty();
             let mut attenuation: Color = Color::new_empty();
-            if hit_rec.mat.clone().expect("shouldn't crash rite").scatter(
-                ray,
+            if !hit_rec.mat.clone().expect("shouldn't crash rite").scatter(
+                &ray,
                 &hit_rec,
                 &mut attenuation,
                 &mut scattered_ray,
+                sampler,
             ) {
-                return attenuation * self.ray_color(&scattered_ray, world, depth - 1);
+                return Color::new_empty();
+            }
+            throughput = throughput * attenuation;
+            ray = scattered_ray;
+
+            //russian roulette: past the first few bounces kill dim paths at random and boost the
+            //survivors by 1/p, which keeps the estimate unbiased
+            if bounce >= self.rr_min_depth {
+                let p = throughput
+                    .x()
+                    .max(throughput.y())
+                    .max(throughput.z())
+                    .min(0.95);
+                if sampler.get_1d() >= p {
+                    return Color::new_empty();
+                }
+                throughput = (1_f64 / p) * throughput;
             }
-            return Color::new(0_f64, 0_f64, 0_f64);
         }
-        let unit_dir: Vec3 = unit_vector(&ray.dir());
-        let a = 0.5_f64 * (unit_dir.y() + 1_f64);
-        (1_f64 - a) * Color::new(1_f64, 1_f64, 1_f64) + a * Color::new(0.5_f64, 0.7_f64, 1_f64)
+        Color::new_empty()
     }
 
-    fn get_ray(&self, x: i32, y: i32) -> Ray {
-        // Construct a camera ray originating from the origin and directed at randomly sampled
-        // point around the pixel location i, j.
-        let offset = self.sample_square();
+    //None when the sample falls outside what the projection covers (e.g. the fisheye circle),
+    //without a sampler the ray leaves from the middle of the lens
+    fn get_ray(
+        &self,
+        x: i32,
+        y: i32,
+        offset: &Vec3,
+        sampler: Option<&mut dyn Sampler>,
+    ) -> Option<Ray> {
+        // Construct a camera ray originating from the origin and directed at the sampled
+        // point offset from the pixel location i, j.
         let pixel_sample = self.pixel00_loc
             + ((x as f64 + offset.x()) * self.pixel_delta_u)
             + ((y as f64 + offset.y()) * self.pixel_delta_v);
-        let ray_origin = match self.defocus_angle {
-            x if x <= 0_f64 => self.center,
-            _ => self.defocus_disk_sample(),
-        };
-        let ray_dir = pixel_sample - ray_origin;
-        Ray::new(ray_origin, ray_dir)
+        //film position in [0,1]^2, t going down the image
+        let s = (x as f64 + 0.5 + offset.x()) / self.img_width as f64;
+        let t = (y as f64 + 0.5 + offset.y()) / self.img_height as f64;
+
+        match self.projection {
+            Projection::Perspective => {
+                let pinhole = self.physical.is_none() && self.defocus_angle <= 0_f64;
+                let ray_origin = match (pinhole, sampler) {
+                    (false, Some(sampler)) => self.defocus_disk_sample(sampler),
+                    _ => self.center,
+                };
+                let ray_dir = pixel_sample - ray_origin;
+                Some(Ray::new(ray_origin, ray_dir))
+            }
+            Projection::Orthographic => {
+                //start on the camera plane straight behind the pixel's spot on the focus plane
+                Some(Ray::new(pixel_sample + self.focus_dist * self.w, -self.w))
+            }
+            Projection::Fisheye => {
+                let aspect = self.img_width as f64 * self.pixel_aspect / self.img_height as f64;
+                let nx = (2_f64 * s - 1_f64) * aspect;
+                let ny = 1_f64 - 2_f64 * t;
+                let r = (nx * nx + ny * ny).sqrt();
+                if r > 1_f64 {
+                    return None;
+                }
+                //equidistant: angle off the view axis grows linearly with distance from center
+                let theta = r * deg_to_rad(self.fisheye_fov) / 2_f64;
+                let phi = ny.atan2(nx);
+                let ray_dir =
+                    theta.sin() * (phi.cos() * self.u + phi.sin() * self.v) - theta.cos() * self.w;
+                Some(Ray::new(self.center, ray_dir))
+            }
+            Projection::Equirectangular => {
+                let lon = (s - 0.5) * 2_f64 * PI;
+                let lat = (0.5 - t) * PI;
+                let ray_dir =
+                    lat.cos() * (lon.sin() * self.u - lon.cos() * self.w) + lat.sin() * self.v;
+                //omnidirectional stereo: the eye orbits the head to stay perpendicular to the
+                //horizontal view direction, so every column gets the right parallax
+                let eye_dir = lon.cos() * self.u + lon.sin() * self.w;
+                let ray_origin = self.lookfrom + (self.eye * self.interocular) * eye_dir;
+                Some(Ray::new(ray_origin, ray_dir))
+            }
+        }
     }
 
-    fn sample_square(&self) -> Vec3 {
+    fn sample_square(&self, sampler: &mut dyn Sampler) -> Vec3 {
         // Returns the vector to a random point in the [-.5,-.5]-[+.5,+.5] unit square.
-        Vec3::new(random_f64() - 0.5, random_f64() - 0.5, 0_f64)
+        let (ux, uy) = sampler.get_2d();
+        Vec3::new(ux - 0.5, uy - 0.5, 0_f64)
     }
 
-    fn defocus_disk_sample(&self) -> Point3 {
-        let p = random_in_unit_disk();
+    fn defocus_disk_sample(&self, sampler: &mut dyn Sampler) -> Point3 {
+        let p = self.aperture.sample(sampler.get_2d());
         self.center + (p.x() * self.defocus_disk_u) + (p.y() * self.defocus_disk_v)
     }
 
-    pub fn adjust_view(&mut self, yaw_delta: f64, pitch_delta: f64) {
-        //calculate current direction vector
-        let direction = self.lookat - self.lookfrom;
-        let distance = direction.get_len();
-        let mut dir_normalized = unit_vector(&direction);
-
-        //convert to spherical coordinates
-        //gonna be real idk why there's inverse trig functions here dawg
-        let theta = dir_normalized.z().atan2(dir_normalized.x());
-        let phi = dir_normalized.y().asin();
-
-        // Apply deltas with clamping
-        let new_theta = theta + yaw_delta;
-        let new_phi = (phi + pitch_delta).clamp(
-            -std::f64::consts::FRAC_PI_2 + 0.001,
-            std::f64::consts::FRAC_PI_2 - 0.001,
-        );
-
-        dir_normalized = Vec3::new(
-            new_theta.cos() * new_phi.cos(),
-            new_phi.sin(),
-            new_theta.sin() * new_phi.cos(),
-        );
-
-        //update lookat point while maintaining focus distance
-        self.lookat = self.lookfrom + (distance * dir_normalized);
-        // recalculate camera basis vectors
-        self.update_basis_vectors();
-    }
-
-    fn update_basis_vectors(&mut self) {
-        //recalculate orthonormal basis
-        self.w = unit_vector(&(self.lookfrom - self.lookat));
-        self.u = unit_vector(&cross(&self.vup, &self.w));
-        self.v = cross(&self.w, &self.u);
+    //closest hit of the ray through the middle of pixel (x, y), needs init
+    fn primary_hit(&self, world: &dyn Hittable, x: i32, y: i32) -> Option<(Ray, HitRecord)> {
+        let ray = self.get_ray(x, y, &Vec3::new_empty(), None)?;
+        let mut hit_rec: HitRecord = HitRecord::new_empty();
+        match world.hit(&ray, Interval::new(0.001, INFINITY), &mut hit_rec) {
+            true => Some((ray, hit_rec)),
+            false => None,
+        }
+    }
 
-        //update viewport parameters
-        let viewport_height = 2.0 * (self.vfov.to_radians() / 2.0).tan();
-        let viewport_width = viewport_height * self.aspect_ratio;
+    //cast a ray through the middle of pixel (x, y) and report the closest thing it hits
+    pub fn pick(&mut self, world: &dyn Hittable, x: i32, y: i32) -> Option<Pick> {
+        self.init();
+        if x < 0 || y < 0 || x >= self.img_width || y >= self.img_height {
+            return None;
+        }
+        let (ray, hit_rec) = self.primary_hit(world, x, y)?;
+        let to_hit = hit_rec.p - ray.origin();
+        Some(Pick {
+            object_id: hit_rec.object_id?,
+            distance: to_hit.get_len(),
+            depth: dot(&to_hit, &(-self.w)),
+        })
+    }
 
-        //calculate horizontal/vertical viewport vectors
-        let viewport_u = viewport_width * self.u;
-        let viewport_v = viewport_height * (-self.v);
+    //object id under the middle of every pixel, row by row
+    pub fn object_ids(&mut self, world: &dyn Hittable) -> Vec<Option<usize>> {
+        self.init();
+        let mut ids = Vec::with_capacity((self.img_width * self.img_height) as usize);
+        for y in 0..self.img_height {
+            for x in 0..self.img_width {
+                ids.push(
+                    self.primary_hit(world, x, y)
+                        .and_then(|(_, hit)| hit.object_id),
+                );
+            }
+        }
+        ids
+    }
 
-        //pixel delta vectors
-        self.pixel_delta_u = viewport_u / self.img_width as f64;
-        self.pixel_delta_v = viewport_v / self.img_height as f64;
+    //depth along the view direction under the middle of every pixel, row by row, INFINITY
+    //where the ray escapes to the sky
+    pub fn depths(&mut self, world: &dyn Hittable) -> Vec<f64> {
+        self.init();
+        let mut depths = Vec::with_capacity((self.img_width * self.img_height) as usize);
+        for y in 0..self.img_height {
+            for x in 0..self.img_width {
+                depths.push(match self.primary_hit(world, x, y) {
+                    Some((ray, hit)) => dot(&(hit.p - ray.origin()), &(-self.w)),
+                    None => INFINITY,
+                });
+            }
+        }
+        depths
+    }
 
-        let viewport_upper_left =
-            self.center - (self.focus_dist * self.w) - viewport_u / 2_f64 - viewport_v / 2_f64;
-        self.pixel00_loc =
-            viewport_upper_left + 0.5_f64 * (self.pixel_delta_u + self.pixel_delta_v);
+    //the point `depth` along the view direction behind the middle of pixel (x, y), for a
+    //perspective camera that has rendered (or been initialized by depths)
+    pub fn pixel_point(&self, x: i32, y: i32, depth: f64) -> Point3 {
+        let pixel =
+            self.pixel00_loc + (x as f64 * self.pixel_delta_u) + (y as f64 * self.pixel_delta_v);
+        self.center + (depth / self.focus_dist) * (pixel - self.center)
+    }
 
-        //update defocus disk basis
-        let defocus_radius: f64 = self.focus_dist * (deg_to_rad(self.defocus_angle / 2_f64).tan());
-        self.defocus_disk_u = defocus_radius * self.u;
-        self.defocus_disk_v = defocus_radius * self.u;
+    //inverse of pixel_point: where a point lands on the image, in pixels with pixel centers on
+    //whole numbers, and its depth. None behind the camera and for anything but mono perspective
+    pub fn project(&self, p: Point3) -> Option<(f64, f64, f64)> {
+        if self.projection != Projection::Perspective || self.stereo != StereoMode::Mono {
+            return None;
+        }
+        let to_p = p - self.center;
+        let depth = dot(&to_p, &(-self.w));
+        if depth <= 1e-6 {
+            return None;
+        }
+        let on_plane = self.center + (self.focus_dist / depth) * to_p - self.pixel00_loc;
+        Some((
+            dot(&on_plane, &self.pixel_delta_u) / self.pixel_delta_u.get_len_squared(),
+            dot(&on_plane, &self.pixel_delta_v) / self.pixel_delta_v.get_len_squared(),
+            depth,
+        ))
     }
 
-    pub fn move_fwd(&mut self, speed: f64) {
-        let direction = unit_vector(&(self.lookat - self.lookfrom));
-        self.lookfrom += speed * direction;
-        self.lookat += speed * direction;
+    //in radians, see Quat::yaw_pitch_roll
+    pub fn yaw_pitch_roll(&self) -> (f64, f64, f64) {
+        self.orientation.yaw_pitch_roll()
+    }
+
+    pub fn forward(&self) -> Vec3 {
+        self.orientation.forward()
+    }
+
+    pub fn right(&self) -> Vec3 {
+        self.orientation.right()
+    }
+
+    //point the camera from `from` at `at`, with `up` picking the roll
+    pub fn look_at(&mut self, from: Point3, at: Point3, up: Vec3) {
+        let back = unit_vector(&(from - at));
+        let right = unit_vector(&cross(&up, &back));
+        let cam_up = cross(&back, &right);
+        self.lookfrom = from;
+        self.look_dist = (at - from).get_len();
+        self.orientation = Quat::from_basis(&right, &cam_up, &back);
+        self.sync_look();
+    }
+
+    //re-derive lookat/vup after lookfrom or the orientation changed
+    fn sync_look(&mut self) {
+        self.lookat = self.lookfrom + self.look_dist * self.orientation.forward();
+        self.vup = self.orientation.up();
+    }
+
+    //yaws about world up and pitches about the camera's own right axis, so looking around
+    //never tilts the horizon and there's still no clamp at the poles. Only roll() tilts it
+    pub fn adjust_view(&mut self, yaw_delta: f64, pitch_delta: f64) {
+        let yaw = Quat::from_axis_angle(&Vec3::new(0_f64, 1_f64, 0_f64), -yaw_delta);
+        let pitch = Quat::from_axis_angle(&Vec3::new(1_f64, 0_f64, 0_f64), pitch_delta);
+        self.orientation = (yaw * self.orientation * pitch).normalized();
+        self.sync_look();
     }
 
-    pub fn move_backward(&mut self, speed: f64) {
-        let direction = unit_vector(&(self.lookat - self.lookfrom)); //forward direction
-        self.lookfrom += -(speed * direction); //move backward (opposite of forward)
-        self.lookat += -(speed * direction);
+    //positive rolls clockwise (right side down)
+    pub fn roll(&mut self, roll_delta: f64) {
+        let roll = Quat::from_axis_angle(&Vec3::new(0_f64, 0_f64, 1_f64), -roll_delta);
+        self.orientation = (self.orientation * roll).normalized();
+        self.sync_look();
+    }
+
+    //put the eye at p, keeping the orientation
+    pub fn set_position(&mut self, p: Point3) {
+        self.lookfrom = p;
+        self.sync_look();
+    }
+
+    pub fn move_fwd(&mut self, speed: f64) {
+        self.lookfrom += speed * self.orientation.forward();
+        self.sync_look();
     }
 
     pub fn move_right(&mut self, speed: f64) {
-        let right = unit_vector(&cross(&(self.lookat - self.lookfrom), &self.v)); //right direction (perpendicular to forward and up)
-        self.lookfrom += speed * right;
-        self.lookat += speed * right;
+        self.lookfrom += speed * self.orientation.right();
+        self.sync_look();
+    }
+
+    //straight up in the world, however the camera is pitched or rolled
+    pub fn move_up(&mut self, speed: f64) {
+        self.lookfrom += speed * Vec3::new(0_f64, 1_f64, 0_f64);
+        self.sync_look();
     }
+}
+
+//blue at 0 through green to red at 1
+fn heat(t: f64) -> Color {
+    let t = t.clamp(0_f64, 1_f64);
+    Color::new(
+        (2_f64 * t - 1_f64).max(0_f64),
+        1_f64 - (2_f64 * t - 1_f64).abs(),
+        (1_f64 - 2_f64 * t).max(0_f64),
+    )
+}
+
+//a stable, fairly saturated color for an object id
+fn id_color(id: usize) -> Color {
+    let mut h = (id as u64 + 1).wrapping_mul(0x9e37_79b9_7f4a_7c15);
+    h ^= h >> 29;
+    let channel = |shift: u32| 0.2 + 0.8 * ((h >> shift) & 0xff) as f64 / 255_f64;
+    Color::new(channel(0), channel(8), channel(16))
+}
 
-    pub fn move_left(&mut self, speed: f64) {
-        let right = unit_vector(&cross(&(self.lookat - self.lookfrom), &self.v)); //left direction (perpendicular to forward and up)
-        self.lookfrom += -(speed * right); //left left (opposite of right)
-        self.lookat += -(speed * right);
+//copy a src_w x src_h image into dst (dst_w wide) at (x0, y0), clipped to dst
+#[allow(clippy::too_many_arguments)]
+fn blit<T: Copy>(
+    dst: &mut [T],
+    dst_w: i32,
+    src: &[T],
+    src_w: i32,
+    src_h: i32,
+    x0: i32,
+    y0: i32,
+    channels: usize,
+) {
+    let dst_h = dst.len() as i32 / (dst_w * channels as i32);
+    let cols = src_w.min(dst_w - x0).max(0) as usize * channels;
+    for row in 0..src_h.min(dst_h - y0) {
+        let src_start = (row * src_w) as usize * channels;
+        let dst_start = ((row + y0) * dst_w + x0) as usize * channels;
+        dst[dst_start..dst_start + cols].copy_from_slice(&src[src_start..src_start + cols]);
     }
 }
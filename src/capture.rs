@@ -0,0 +1,64 @@
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+
+use crate::{
+    camera::{Camera, RenderControl},
+    color::write_color,
+    image_io,
+    ray::HittableList,
+};
+
+// High quality render of a camera running on its own thread, so the viewer stays interactive.
+// Saves <prefix>.png and the linear <prefix>.exr when done.
+pub struct Capture {
+    pub prefix: String,
+    control: Arc<RenderControl>,
+    handle: Option<JoinHandle<Result<(), String>>>,
+}
+
+impl Capture {
+    pub fn start(mut cam: Camera, prefix: &str, world: Arc<HittableList>) -> Self {
+        let control = Arc::new(RenderControl::default());
+        cam.control = Some(control.clone());
+        let out = prefix.to_string();
+        let handle = thread::spawn(move || {
+            let pixels = cam.render_linear(world.as_ref());
+            let (width, height) = cam.dimensions();
+            let rgb: Vec<u8> = pixels
+                .iter()
+                .flat_map(|p| write_color(p).map(|c| c as u8))
+                .collect();
+            image_io::write_png(&format!("{}.png", out), width as u32, height as u32, &rgb)?;
+            image_io::write_exr(
+                &format!("{}.exr", out),
+                width as u32,
+                height as u32,
+                &pixels,
+            )
+            .map_err(|e| e.to_string())
+        });
+        Self {
+            prefix: prefix.to_string(),
+            control,
+            handle: Some(handle),
+        }
+    }
+
+    //0 to 1
+    pub fn progress(&self) -> f64 {
+        self.control.progress()
+    }
+
+    //the outcome once the render thread is done, None while it's still going
+    pub fn poll(&mut self) -> Option<Result<(), String>> {
+        if !self.handle.as_ref()?.is_finished() {
+            return None;
+        }
+        let handle = self.handle.take()?;
+        Some(
+            handle
+                .join()
+                .unwrap_or_else(|_| Err("render thread panicked".to_string())),
+        )
+    }
+}
@@ -28,3 +28,8 @@ pub fn write_color(pixel_color: &Color) -> [u32; 3] {
     let ib: u32 = (256.0 * intensity.clamp(b)) as u32;
     [ir, ig, ib]
 }
+
+//relative luminance (rec. 709 weights) of a linear color
+pub fn luminance(pixel_color: &Color) -> f64 {
+    0.2126 * pixel_color.x() + 0.7152 * pixel_color.y() + 0.0722 * pixel_color.z()
+}
@@ -0,0 +1,52 @@
+use crate::camera::Camera;
+
+// Where every size in the viewer comes from. The window can be resized or made fullscreen, the
+// camera renders one pixel for every pixel_scale x pixel_scale block of it with the window's
+// aspect ratio, and the streaming texture is whatever size the camera renders at.
+pub struct Display {
+    pub window_width: u32,
+    pub window_height: u32,
+    pub pixel_scale: u32,
+    pub fullscreen: bool,
+}
+
+impl Display {
+    pub fn new(window_width: u32, window_height: u32, pixel_scale: u32) -> Self {
+        Self {
+            window_width,
+            window_height,
+            pixel_scale: pixel_scale.max(1),
+            fullscreen: false,
+        }
+    }
+
+    pub fn aspect_ratio(&self) -> f64 {
+        self.window_width.max(1) as f64 / self.window_height.max(1) as f64
+    }
+
+    pub fn render_width(&self) -> i32 {
+        (self.window_width / self.pixel_scale).max(1) as i32
+    }
+
+    pub fn resize(&mut self, window_width: i32, window_height: i32, cam: &mut Camera) {
+        self.window_width = window_width.max(1) as u32;
+        self.window_height = window_height.max(1) as u32;
+        self.apply(cam);
+    }
+
+    pub fn apply(&self, cam: &mut Camera) {
+        cam.set_aspect_ratio(self.aspect_ratio());
+        cam.set_width(self.render_width());
+    }
+
+    //window coordinates to the camera pixel under them
+    pub fn to_render_pixel(&self, x: i32, y: i32, cam: &Camera) -> (i32, i32) {
+        let (width, height) = cam.dimensions();
+        let px = x as i64 * width as i64 / self.window_width.max(1) as i64;
+        let py = y as i64 * height as i64 / self.window_height.max(1) as i64;
+        (
+            (px as i32).clamp(0, width - 1),
+            (py as i32).clamp(0, height - 1),
+        )
+    }
+}
@@ -0,0 +1,111 @@
+use crate::{
+    camera::{Camera, Pick},
+    input::Action,
+    scene::Scene,
+    vec3::{unit_vector, Vec3},
+};
+
+const MOVE_STEP: f64 = 0.25;
+const SCALE_STEP: f64 = 1.1;
+const HIGHLIGHT: [u8; 3] = [255, 220, 0];
+
+// In-viewer scene editing. While active, clicking selects the object under the cursor and the
+// edit_* actions change it (default keys in InputConfig::default_config):
+//   arrows            move along the ground, relative to where the camera faces
+//   page up/down      move up/down
+//   =/- or the wheel  scale
+//   delete/backspace  delete, insert duplicates next to it
+//   M                 swap material, F2 (save_scene) saves the scene
+pub struct Editor {
+    pub active: bool,
+    pub selected: Option<usize>,
+    scene_path: String,
+}
+
+impl Editor {
+    pub fn new(scene_path: &str) -> Self {
+        Self {
+            active: false,
+            selected: None,
+            scene_path: scene_path.to_string(),
+        }
+    }
+
+    pub fn select(&mut self, pick: Option<Pick>, scene: &Scene) {
+        //anything past the scene's objects (e.g. the other player) can't be edited
+        self.selected = pick
+            .map(|p| p.object_id)
+            .filter(|&id| id < scene.objects.len());
+        println!("selected: {:?}", self.selected);
+    }
+
+    //returns true when the scene changed and the world has to be rebuilt
+    pub fn handle_action(&mut self, action: Action, scene: &mut Scene, cam: &Camera) -> bool {
+        if action == Action::SaveScene {
+            match scene.save(&self.scene_path) {
+                Ok(_) => println!("saved scene to {}", self.scene_path),
+                Err(e) => println!("couldn't save scene: {}", e),
+            }
+            return false;
+        }
+        let Some(id) = self.selected else {
+            return false;
+        };
+
+        //move in the ground plane so the arrows don't dig objects into the floor
+        let flatten = |v: Vec3| unit_vector(&Vec3::new(v.x(), 0_f64, v.z()));
+        let forward = flatten(cam.forward());
+        let right = flatten(cam.right());
+        let object = &mut scene.objects[id];
+        match action {
+            Action::EditMoveForward => object.center += MOVE_STEP * forward,
+            Action::EditMoveBack => object.center += -(MOVE_STEP * forward),
+            Action::EditMoveRight => object.center += MOVE_STEP * right,
+            Action::EditMoveLeft => object.center += -(MOVE_STEP * right),
+            Action::EditMoveUp => object.center += Vec3::new(0_f64, MOVE_STEP, 0_f64),
+            Action::EditMoveDown => object.center += Vec3::new(0_f64, -MOVE_STEP, 0_f64),
+            Action::EditScaleUp => object.radius *= SCALE_STEP,
+            Action::EditScaleDown => object.radius /= SCALE_STEP,
+            Action::EditCycleMaterial => object.material = object.material.next_in_palette(),
+            Action::EditDuplicate => {
+                let mut copy = *object;
+                copy.center += (2.5 * copy.radius) * right;
+                scene.objects.push(copy);
+                self.selected = Some(scene.objects.len() - 1);
+            }
+            Action::EditDelete => {
+                scene.objects.remove(id);
+                self.selected = None;
+            }
+            _ => return false,
+        }
+        true
+    }
+}
+
+//outline object `selected` in a rendered image and tint its inside, ids come from
+//Camera::object_ids. Not a method so the render thread can use it without the editor.
+pub fn highlight(selected: usize, image: &mut [u8], ids: &[Option<usize>], width: usize) {
+    let is_selected = |i: usize| ids[i] == Some(selected);
+    for i in 0..ids.len() {
+        if !is_selected(i) {
+            continue;
+        }
+        let (x, y) = (i % width, i / width);
+        let edge = x == 0
+            || y == 0
+            || x + 1 == width
+            || i + width >= ids.len()
+            || !is_selected(i - 1)
+            || !is_selected(i + 1)
+            || !is_selected(i - width)
+            || !is_selected(i + width);
+        for c in 0..3 {
+            let px = &mut image[i * 3 + c];
+            *px = match edge {
+                true => HIGHLIGHT[c],
+                false => ((*px as u32 * 3 + HIGHLIGHT[c] as u32) / 4) as u8,
+            };
+        }
+    }
+}
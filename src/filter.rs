@@ -0,0 +1,105 @@
+use crate::helper::PI;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FilterKind {
+    Box,
+    Tent,
+    Gaussian,
+    Mitchell,
+    Lanczos,
+}
+
+impl FilterKind {
+    pub fn next(self) -> Self {
+        match self {
+            FilterKind::Box => FilterKind::Tent,
+            FilterKind::Tent => FilterKind::Gaussian,
+            FilterKind::Gaussian => FilterKind::Mitchell,
+            FilterKind::Mitchell => FilterKind::Lanczos,
+            FilterKind::Lanczos => FilterKind::Box,
+        }
+    }
+
+    //radius (in pixels) each filter is usually run with
+    pub fn default_radius(self) -> f64 {
+        match self {
+            FilterKind::Box => 0.5,
+            FilterKind::Tent => 1.0,
+            FilterKind::Gaussian => 1.5,
+            FilterKind::Mitchell => 2.0,
+            FilterKind::Lanczos => 2.0,
+        }
+    }
+}
+
+// Pixel reconstruction filter. Every camera sample gets splatted into all pixels whose center
+// lies within `radius` of it, weighted by the filter evaluated at the offset, and each pixel is
+// normalised by the sum of the weights it received.
+#[derive(Clone, Copy, Debug)]
+pub struct Filter {
+    pub kind: FilterKind,
+    pub radius: f64,
+}
+
+impl Filter {
+    pub fn new(kind: FilterKind, radius: f64) -> Self {
+        Self {
+            kind,
+            radius: radius.max(0.5),
+        }
+    }
+
+    //filters are separable, dx/dy are the pixel center minus the sample position in pixels
+    pub fn evaluate(&self, dx: f64, dy: f64) -> f64 {
+        self.evaluate_1d(dx) * self.evaluate_1d(dy)
+    }
+
+    fn evaluate_1d(&self, x: f64) -> f64 {
+        let r = self.radius;
+        if x.abs() > r {
+            return 0_f64;
+        }
+        match self.kind {
+            //half open so a sample on the boundary between two pixels only lands in one
+            FilterKind::Box => match x {
+                x if x > -r => 1_f64,
+                _ => 0_f64,
+            },
+            FilterKind::Tent => r - x.abs(),
+            FilterKind::Gaussian => {
+                let sigma = r / 3_f64;
+                let gaussian = |v: f64| (-(v * v) / (2_f64 * sigma * sigma)).exp();
+                //shift down so the filter goes to zero at the radius instead of being cut off
+                (gaussian(x) - gaussian(r)).max(0_f64)
+            }
+            FilterKind::Mitchell => Self::mitchell_1d(2_f64 * x / r),
+            FilterKind::Lanczos => Self::sinc(x) * Self::sinc(x / r),
+        }
+    }
+
+    //Mitchell-Netravali with B = C = 1/3, defined on [-2, 2]
+    fn mitchell_1d(x: f64) -> f64 {
+        let (b, c) = (1_f64 / 3_f64, 1_f64 / 3_f64);
+        let x = x.abs();
+        let value = if x < 1_f64 {
+            (12_f64 - 9_f64 * b - 6_f64 * c) * x.powi(3)
+                + (-18_f64 + 12_f64 * b + 6_f64 * c) * x.powi(2)
+                + (6_f64 - 2_f64 * b)
+        } else if x < 2_f64 {
+            (-b - 6_f64 * c) * x.powi(3)
+                + (6_f64 * b + 30_f64 * c) * x.powi(2)
+                + (-12_f64 * b - 48_f64 * c) * x
+                + (8_f64 * b + 24_f64 * c)
+        } else {
+            0_f64
+        };
+        value / 6_f64
+    }
+
+    fn sinc(x: f64) -> f64 {
+        if x.abs() < 1e-5 {
+            return 1_f64;
+        }
+        (PI * x).sin() / (PI * x)
+    }
+}
@@ -0,0 +1,95 @@
+use sdl2::{
+    controller::{Axis, Button, GameController},
+    event::Event,
+    GameControllerSubsystem,
+};
+
+// Connected game controllers, opened as they get plugged in. Sticks are read every frame:
+//   left stick     move, forward/back and strafe
+//   triggers       right goes up, left goes down
+//   right stick    look
+// buttons are bound to actions like keys are, see InputConfig.
+pub struct Gamepads {
+    subsystem: GameControllerSubsystem,
+    controllers: Vec<GameController>,
+}
+
+impl Gamepads {
+    pub fn new(subsystem: GameControllerSubsystem) -> Self {
+        //controllers present at startup also arrive as ControllerDeviceAdded events
+        Self {
+            subsystem,
+            controllers: Vec::new(),
+        }
+    }
+
+    pub fn handle_event(&mut self, event: &Event) {
+        match *event {
+            Event::ControllerDeviceAdded { which, .. } => match self.subsystem.open(which) {
+                Ok(controller) => {
+                    println!("controller connected: {}", controller.name());
+                    self.controllers.push(controller);
+                }
+                Err(e) => println!("couldn't open controller {}: {}", which, e),
+            },
+            Event::ControllerDeviceRemoved { which, .. } => {
+                self.controllers.retain(|c| c.instance_id() != which);
+                println!("controller disconnected");
+            }
+            _ => {}
+        }
+    }
+
+    pub fn is_button_held(&self, button: Button) -> bool {
+        self.controllers.iter().any(|c| c.button(button))
+    }
+
+    //strongest deflection of the stick over all controllers, after dead zone and curve
+    pub fn stick(&self, x: Axis, y: Axis, deadzone: f64, exponent: f64) -> (f64, f64) {
+        let mut best = (0_f64, 0_f64);
+        for controller in &self.controllers {
+            let (sx, sy) = shape_stick(
+                axis_value(controller, x),
+                axis_value(controller, y),
+                deadzone,
+                exponent,
+            );
+            if sx * sx + sy * sy > best.0 * best.0 + best.1 * best.1 {
+                best = (sx, sy);
+            }
+        }
+        best
+    }
+
+    pub fn trigger(&self, axis: Axis, deadzone: f64, exponent: f64) -> f64 {
+        self.controllers
+            .iter()
+            .map(|c| shape(axis_value(c, axis), deadzone, exponent))
+            .fold(0_f64, f64::max)
+    }
+}
+
+//axis value in [-1, 1], sticks report y growing downward
+fn axis_value(controller: &GameController, axis: Axis) -> f64 {
+    (controller.axis(axis) as f64 / i16::MAX as f64).clamp(-1_f64, 1_f64)
+}
+
+//maps [deadzone, 1] onto [0, 1] and bends it, exponents above 1 give finer control near center
+fn shape(magnitude: f64, deadzone: f64, exponent: f64) -> f64 {
+    if magnitude <= deadzone {
+        return 0_f64;
+    }
+    ((magnitude - deadzone) / (1_f64 - deadzone))
+        .clamp(0_f64, 1_f64)
+        .powf(exponent)
+}
+
+//radial dead zone, so diagonals aren't clipped the way per-axis dead zones clip them
+fn shape_stick(x: f64, y: f64, deadzone: f64, exponent: f64) -> (f64, f64) {
+    let magnitude = (x * x + y * y).sqrt();
+    if magnitude <= deadzone {
+        return (0_f64, 0_f64);
+    }
+    let scale = shape(magnitude.min(1_f64), deadzone, exponent) / magnitude;
+    (x * scale, y * scale)
+}
@@ -1,18 +1,48 @@
-use rand::Rng;
+use rand::{Rng as _, SeedableRng};
+use rand_chacha::ChaCha8Rng;
 
 pub const INFINITY: f64 = f64::INFINITY;
 pub const PI: f64 = std::f64::consts::PI;
 
+//every random number in a render comes from one of these, never from a global/thread rng.
+//A named algorithm rather than StdRng, which rand may swap out between versions, so a seed
+//keeps giving the same image across dependency upgrades
+pub type Rng = ChaCha8Rng;
+
 pub fn deg_to_rad(degrees: f64) -> f64 {
     degrees * PI / 180_f64
 }
 
-pub fn random_f64() -> f64 {
-    let mut rng = rand::thread_rng();
-    rng.gen::<f64>()
+pub fn rad_to_deg(radians: f64) -> f64 {
+    radians * 180_f64 / PI
+}
+
+//splitmix64 finalizer, used to scatter neighbouring seeds/stream ids across the whole u64 range
+fn mix_seed(mut z: u64) -> u64 {
+    z = z.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+//independent generator for one stream (e.g. one pixel) of a render with the given seed,
+//so the result only depends on (seed, stream) and not on what order or thread it runs in
+pub fn stream_rng(seed: u64, stream: u64) -> Rng {
+    ChaCha8Rng::seed_from_u64(mix_seed(seed ^ mix_seed(stream)))
+}
+
+//combine several values (seed, pixel coords, dimension...) into one well mixed 64 bit hash
+pub fn hash(values: &[u64]) -> u64 {
+    values
+        .iter()
+        .fold(0x243F_6A88_85A3_08D3, |h, &v| mix_seed(h ^ v))
+}
+
+//map a hash onto [0, 1) using its top 53 bits
+pub fn hash_to_f64(h: u64) -> f64 {
+    (h >> 11) as f64 * (1.0 / (1_u64 << 53) as f64)
 }
 
-pub fn random_f64_range(min: f64, max: f64) -> f64 {
-    let mut rng = rand::thread_rng();
-    min + (max - min) * rng.gen::<f64>()
+pub fn random_f64(rng: &mut Rng) -> f64 {
+    rng.random::<f64>()
 }
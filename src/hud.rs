@@ -0,0 +1,116 @@
+use sdl2::{
+    pixels::Color as SdlColor,
+    rect::Rect,
+    render::{BlendMode, Canvas},
+    video::Window,
+};
+
+const SCALE: i32 = 3; //screen pixels per font pixel
+const ADVANCE: i32 = 4 * SCALE; //3 wide glyphs plus a gap
+const LINE_HEIGHT: i32 = 6 * SCALE;
+const MARGIN: i32 = 2 * SCALE;
+
+// Text overlay drawn straight onto the canvas after the rendered image, with a tiny built in 3x5
+// font so it doesn't need any font files. Text is shown uppercase. visible only toggles the
+// stats, the caller still draws any messages it has when it's off.
+pub struct Hud {
+    pub visible: bool,
+}
+
+impl Hud {
+    pub fn new() -> Self {
+        Self { visible: true }
+    }
+
+    pub fn draw(&self, canvas: &mut Canvas<Window>, lines: &[String]) -> Result<(), String> {
+        if lines.is_empty() {
+            return Ok(());
+        }
+        let widest = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0) as i32;
+        canvas.set_blend_mode(BlendMode::Blend);
+        canvas.set_draw_color(SdlColor::RGBA(0, 0, 0, 160));
+        canvas.fill_rect(Rect::new(
+            0,
+            0,
+            (widest * ADVANCE + 2 * MARGIN) as u32,
+            (lines.len() as i32 * LINE_HEIGHT + 2 * MARGIN - SCALE) as u32,
+        ))?;
+
+        let mut pixels: Vec<Rect> = Vec::new();
+        for (row, line) in lines.iter().enumerate() {
+            let y0 = MARGIN + row as i32 * LINE_HEIGHT;
+            for (col, c) in line.chars().enumerate() {
+                let x0 = MARGIN + col as i32 * ADVANCE;
+                for (gy, bits) in glyph(c).iter().enumerate() {
+                    for gx in 0..3 {
+                        if bits & (0b100 >> gx) != 0 {
+                            pixels.push(Rect::new(
+                                x0 + gx * SCALE,
+                                y0 + gy as i32 * SCALE,
+                                SCALE as u32,
+                                SCALE as u32,
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+        canvas.set_draw_color(SdlColor::RGB(255, 255, 255));
+        canvas.fill_rects(&pixels)
+    }
+}
+
+//rows top to bottom, the high bit of each row is the leftmost pixel
+fn glyph(c: char) -> [u8; 5] {
+    match c.to_ascii_uppercase() {
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b111, 0b001, 0b111, 0b100, 0b111],
+        '3' => [0b111, 0b001, 0b111, 0b001, 0b111],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b111, 0b001, 0b111],
+        '6' => [0b111, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b001, 0b010, 0b010],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b111],
+        'A' => [0b010, 0b101, 0b111, 0b101, 0b101],
+        'B' => [0b110, 0b101, 0b110, 0b101, 0b110],
+        'C' => [0b011, 0b100, 0b100, 0b100, 0b011],
+        'D' => [0b110, 0b101, 0b101, 0b101, 0b110],
+        'E' => [0b111, 0b100, 0b110, 0b100, 0b111],
+        'F' => [0b111, 0b100, 0b110, 0b100, 0b100],
+        'G' => [0b011, 0b100, 0b101, 0b101, 0b011],
+        'H' => [0b101, 0b101, 0b111, 0b101, 0b101],
+        'I' => [0b111, 0b010, 0b010, 0b010, 0b111],
+        'J' => [0b001, 0b001, 0b001, 0b101, 0b010],
+        'K' => [0b101, 0b101, 0b110, 0b101, 0b101],
+        'L' => [0b100, 0b100, 0b100, 0b100, 0b111],
+        'M' => [0b101, 0b111, 0b111, 0b101, 0b101],
+        'N' => [0b110, 0b101, 0b101, 0b101, 0b101],
+        'O' => [0b010, 0b101, 0b101, 0b101, 0b010],
+        'P' => [0b110, 0b101, 0b110, 0b100, 0b100],
+        'Q' => [0b010, 0b101, 0b101, 0b110, 0b011],
+        'R' => [0b110, 0b101, 0b110, 0b101, 0b101],
+        'S' => [0b011, 0b100, 0b010, 0b001, 0b110],
+        'T' => [0b111, 0b010, 0b010, 0b010, 0b010],
+        'U' => [0b101, 0b101, 0b101, 0b101, 0b111],
+        'V' => [0b101, 0b101, 0b101, 0b101, 0b010],
+        'W' => [0b101, 0b101, 0b111, 0b111, 0b101],
+        'X' => [0b101, 0b101, 0b010, 0b101, 0b101],
+        'Y' => [0b101, 0b101, 0b010, 0b010, 0b010],
+        'Z' => [0b111, 0b001, 0b010, 0b100, 0b111],
+        '.' => [0b000, 0b000, 0b000, 0b000, 0b010],
+        ',' => [0b000, 0b000, 0b000, 0b010, 0b100],
+        ':' => [0b000, 0b010, 0b000, 0b010, 0b000],
+        '-' => [0b000, 0b000, 0b111, 0b000, 0b000],
+        '_' => [0b000, 0b000, 0b000, 0b000, 0b111],
+        '+' => [0b000, 0b010, 0b111, 0b010, 0b000],
+        '/' => [0b001, 0b001, 0b010, 0b100, 0b100],
+        '(' => [0b010, 0b100, 0b100, 0b100, 0b010],
+        ')' => [0b010, 0b001, 0b001, 0b001, 0b010],
+        '=' => [0b000, 0b111, 0b000, 0b111, 0b000],
+        '%' => [0b101, 0b001, 0b010, 0b100, 0b101],
+        ' ' => [0b000; 5],
+        _ => [0b111, 0b001, 0b010, 0b000, 0b010], //?
+    }
+}
@@ -0,0 +1,90 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+use crate::color::Color;
+
+//binary ppm (P6), rgb is tightly packed 8 bit rgb rows top to bottom
+pub fn write_ppm(path: &str, width: u32, height: u32, rgb: &[u8]) -> std::io::Result<()> {
+    let mut out = BufWriter::new(File::create(path)?);
+    write!(out, "P6\n{} {}\n255\n", width, height)?;
+    out.write_all(rgb)?;
+    out.flush()
+}
+
+//8 bit rgb png, same layout as write_ppm
+pub fn write_png(path: &str, width: u32, height: u32, rgb: &[u8]) -> Result<(), String> {
+    let file = File::create(path).map_err(|e| e.to_string())?;
+    let mut encoder = png::Encoder::new(BufWriter::new(file), width, height);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().map_err(|e| e.to_string())?;
+    writer.write_image_data(rgb).map_err(|e| e.to_string())
+}
+
+// Uncompressed scanline OpenEXR with 32 bit float R, G, B channels, for keeping the linear
+// radiance of a render (no gamma, no clamping). pixels are rows top to bottom.
+pub fn write_exr(path: &str, width: u32, height: u32, pixels: &[Color]) -> std::io::Result<()> {
+    let attribute = |header: &mut Vec<u8>, name: &str, kind: &str, value: &[u8]| {
+        header.extend_from_slice(name.as_bytes());
+        header.push(0);
+        header.extend_from_slice(kind.as_bytes());
+        header.push(0);
+        header.extend_from_slice(&(value.len() as i32).to_le_bytes());
+        header.extend_from_slice(value);
+    };
+    let i32s = |values: &[i32]| {
+        values
+            .iter()
+            .flat_map(|v| v.to_le_bytes())
+            .collect::<Vec<u8>>()
+    };
+    let f32s = |values: &[f32]| {
+        values
+            .iter()
+            .flat_map(|v| v.to_le_bytes())
+            .collect::<Vec<u8>>()
+    };
+
+    //magic number, then version 2 with no flags (single part scanline file)
+    let mut header: Vec<u8> = vec![0x76, 0x2f, 0x31, 0x01, 2, 0, 0, 0];
+    //channels have to be listed alphabetically: name, float type, linear flag, padding, sampling
+    let mut channels: Vec<u8> = Vec::new();
+    for name in ["B", "G", "R"] {
+        channels.extend_from_slice(name.as_bytes());
+        channels.push(0);
+        channels.extend(i32s(&[2]));
+        channels.extend_from_slice(&[0, 0, 0, 0]);
+        channels.extend(i32s(&[1, 1]));
+    }
+    channels.push(0);
+    let window = i32s(&[0, 0, width as i32 - 1, height as i32 - 1]);
+    attribute(&mut header, "channels", "chlist", &channels);
+    attribute(&mut header, "compression", "compression", &[0]);
+    attribute(&mut header, "dataWindow", "box2i", &window);
+    attribute(&mut header, "displayWindow", "box2i", &window);
+    attribute(&mut header, "lineOrder", "lineOrder", &[0]);
+    attribute(&mut header, "pixelAspectRatio", "float", &f32s(&[1.0]));
+    attribute(&mut header, "screenWindowCenter", "v2f", &f32s(&[0.0, 0.0]));
+    attribute(&mut header, "screenWindowWidth", "float", &f32s(&[1.0]));
+    header.push(0);
+
+    //one block per scanline: y, byte count, then each channel's row in the order listed above
+    let row_bytes = width as u64 * 3 * 4;
+    let first_block = header.len() as u64 + 8 * height as u64;
+    let mut out = BufWriter::new(File::create(path)?);
+    out.write_all(&header)?;
+    for y in 0..height as u64 {
+        out.write_all(&(first_block + y * (8 + row_bytes)).to_le_bytes())?;
+    }
+    for y in 0..height as usize {
+        out.write_all(&(y as i32).to_le_bytes())?;
+        out.write_all(&(row_bytes as i32).to_le_bytes())?;
+        let row = &pixels[y * width as usize..(y + 1) * width as usize];
+        for channel in [2, 1, 0] {
+            for pixel in row {
+                out.write_all(&(pixel.get(channel) as f32).to_le_bytes())?;
+            }
+        }
+    }
+    out.flush()
+}
@@ -0,0 +1,414 @@
+use std::fs;
+
+use sdl2::{
+    controller::{Axis, Button},
+    keyboard::{KeyboardState, Scancode},
+    mouse::{MouseButton, MouseState},
+};
+
+use crate::{gamepad::Gamepads, movement::MoveInput};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    //held
+    MoveForward,
+    MoveBack,
+    MoveLeft,
+    MoveRight,
+    MoveUp,
+    MoveDown,
+    RollLeft,
+    RollRight,
+    LookLeft,
+    LookRight,
+    LookUp,
+    LookDown,
+    Sprint,
+    Slow,
+    //pressed
+    Quit,
+    ToggleWalk,
+    ToggleMouseLock,
+    Pick, //select in edit mode, focus otherwise
+    FocusCenter,
+    ToggleEditMode,
+    CycleSampler,
+    CycleFilter,
+    CycleProjection,
+    CycleStereo,
+    CycleDebugView,
+    CycleAperture,
+    TogglePhysicalLens,
+    OpenAperture,
+    CloseAperture,
+    SaveCubemap,
+    ThrowBall,
+    ToggleHeatmap,
+    ToggleTemporal,
+    ToggleHud,
+    CycleDynamicResolution,
+    ToggleFullscreen,
+    Screenshot,
+    CaptureHighQuality,
+    ReloadInput,
+    //edit mode only, see Editor
+    EditMoveForward,
+    EditMoveBack,
+    EditMoveLeft,
+    EditMoveRight,
+    EditMoveUp,
+    EditMoveDown,
+    EditScaleUp,
+    EditScaleDown,
+    EditCycleMaterial,
+    EditDuplicate,
+    EditDelete,
+    SaveScene,
+}
+
+//names used in the config file
+const ACTION_NAMES: [(Action, &str); 51] = [
+    (Action::MoveForward, "move_forward"),
+    (Action::MoveBack, "move_back"),
+    (Action::MoveLeft, "move_left"),
+    (Action::MoveRight, "move_right"),
+    (Action::MoveUp, "move_up"),
+    (Action::MoveDown, "move_down"),
+    (Action::RollLeft, "roll_left"),
+    (Action::RollRight, "roll_right"),
+    (Action::LookLeft, "look_left"),
+    (Action::LookRight, "look_right"),
+    (Action::LookUp, "look_up"),
+    (Action::LookDown, "look_down"),
+    (Action::Sprint, "sprint"),
+    (Action::Slow, "slow"),
+    (Action::Quit, "quit"),
+    (Action::ToggleWalk, "toggle_walk"),
+    (Action::ToggleMouseLock, "toggle_mouse_lock"),
+    (Action::Pick, "pick"),
+    (Action::FocusCenter, "focus_center"),
+    (Action::ToggleEditMode, "toggle_edit_mode"),
+    (Action::CycleSampler, "cycle_sampler"),
+    (Action::CycleFilter, "cycle_filter"),
+    (Action::CycleProjection, "cycle_projection"),
+    (Action::CycleStereo, "cycle_stereo"),
+    (Action::CycleDebugView, "cycle_debug_view"),
+    (Action::CycleAperture, "cycle_aperture"),
+    (Action::TogglePhysicalLens, "toggle_physical_lens"),
+    (Action::OpenAperture, "open_aperture"),
+    (Action::CloseAperture, "close_aperture"),
+    (Action::SaveCubemap, "save_cubemap"),
+    (Action::ThrowBall, "throw_ball"),
+    (Action::ToggleHeatmap, "toggle_heatmap"),
+    (Action::ToggleTemporal, "toggle_temporal"),
+    (Action::ToggleHud, "toggle_hud"),
+    (Action::CycleDynamicResolution, "cycle_dynamic_resolution"),
+    (Action::ToggleFullscreen, "toggle_fullscreen"),
+    (Action::Screenshot, "screenshot"),
+    (Action::CaptureHighQuality, "capture_high_quality"),
+    (Action::ReloadInput, "reload_input"),
+    (Action::EditMoveForward, "edit_move_forward"),
+    (Action::EditMoveBack, "edit_move_back"),
+    (Action::EditMoveLeft, "edit_move_left"),
+    (Action::EditMoveRight, "edit_move_right"),
+    (Action::EditMoveUp, "edit_move_up"),
+    (Action::EditMoveDown, "edit_move_down"),
+    (Action::EditScaleUp, "edit_scale_up"),
+    (Action::EditScaleDown, "edit_scale_down"),
+    (Action::EditCycleMaterial, "edit_cycle_material"),
+    (Action::EditDuplicate, "edit_duplicate"),
+    (Action::EditDelete, "edit_delete"),
+    (Action::SaveScene, "save_scene"),
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Binding {
+    Key(Scancode),
+    Mouse(MouseButton),
+    WheelUp, //one notch of the mouse wheel
+    WheelDown,
+    Pad(Button),
+}
+
+impl Binding {
+    //SDL key names ("W", "Left Shift", "F5", ...), mouse left|middle|right|x1|x2, wheel up|down or pad
+    //followed by an SDL controller button name (a, b, x, y, start, back, leftshoulder, dpup, ...)
+    fn parse(name: &str) -> Result<Self, String> {
+        if let Some(button) = name.strip_prefix("pad ") {
+            return Button::from_string(button)
+                .map(Binding::Pad)
+                .ok_or(format!("unknown controller button '{}'", button));
+        }
+        let mouse = match name.to_lowercase().as_str() {
+            "mouse left" => Some(MouseButton::Left),
+            "mouse middle" => Some(MouseButton::Middle),
+            "mouse right" => Some(MouseButton::Right),
+            "mouse x1" => Some(MouseButton::X1),
+            "mouse x2" => Some(MouseButton::X2),
+            _ => None,
+        };
+        match name.to_lowercase().as_str() {
+            "wheel up" => return Ok(Binding::WheelUp),
+            "wheel down" => return Ok(Binding::WheelDown),
+            _ => {}
+        }
+        match (mouse, Scancode::from_name(name)) {
+            (Some(button), _) => Ok(Binding::Mouse(button)),
+            (None, Some(scancode)) => Ok(Binding::Key(scancode)),
+            (None, None) => Err(format!("unknown key '{}'", name)),
+        }
+    }
+}
+
+//everything that can be held down this frame
+pub struct HeldInputs<'a> {
+    pub keys: KeyboardState<'a>,
+    pub mouse: MouseState,
+    pub pads: &'a Gamepads,
+}
+
+// Key, mouse and controller bindings plus look settings. Config files are plain text, one entry
+// per line:
+//   bind <action> <key>          e.g. bind sprint Left Shift, bind pick mouse left, bind pick pad a
+//   sensitivity <radians per pixel of mouse movement>
+//   look_speed <radians per second for the look keys and a fully deflected right stick>
+//   stick_deadzone, trigger_deadzone <0 to 1>
+//   stick_exponent <response curve, 1 is linear>
+//   invert_x true|false
+//   invert_y true|false
+// blank lines and lines starting with # are skipped. Actions bound in the file replace all of
+// their default bindings, anything not mentioned keeps the defaults.
+pub struct InputConfig {
+    bindings: Vec<(Action, Binding)>,
+    pub sensitivity: f64,
+    pub look_speed: f64,
+    pub stick_deadzone: f64,
+    pub trigger_deadzone: f64,
+    pub stick_exponent: f64,
+    pub invert_x: bool,
+    pub invert_y: bool,
+}
+
+impl InputConfig {
+    pub fn default_config() -> Self {
+        let key = |action: Action, scancode: Scancode| (action, Binding::Key(scancode));
+        let pad = |action: Action, button: Button| (action, Binding::Pad(button));
+        Self {
+            bindings: vec![
+                key(Action::MoveForward, Scancode::W),
+                key(Action::MoveBack, Scancode::S),
+                key(Action::MoveLeft, Scancode::A),
+                key(Action::MoveRight, Scancode::D),
+                key(Action::MoveUp, Scancode::Space),
+                key(Action::MoveDown, Scancode::LCtrl),
+                key(Action::RollLeft, Scancode::Q),
+                key(Action::RollRight, Scancode::E),
+                key(Action::Sprint, Scancode::LShift),
+                key(Action::Slow, Scancode::LAlt),
+                key(Action::Quit, Scancode::Escape),
+                key(Action::ToggleWalk, Scancode::K),
+                key(Action::ToggleMouseLock, Scancode::L),
+                (Action::Pick, Binding::Mouse(MouseButton::Left)),
+                key(Action::FocusCenter, Scancode::G),
+                key(Action::ToggleEditMode, Scancode::Tab),
+                key(Action::CycleSampler, Scancode::N),
+                key(Action::CycleFilter, Scancode::F),
+                key(Action::CycleProjection, Scancode::P),
+                key(Action::CycleStereo, Scancode::V),
+                key(Action::CycleDebugView, Scancode::I),
+                key(Action::CycleAperture, Scancode::B),
+                key(Action::TogglePhysicalLens, Scancode::O),
+                key(Action::OpenAperture, Scancode::LeftBracket),
+                key(Action::CloseAperture, Scancode::RightBracket),
+                key(Action::SaveCubemap, Scancode::C),
+                key(Action::ThrowBall, Scancode::T),
+                key(Action::ToggleHeatmap, Scancode::H),
+                key(Action::ToggleTemporal, Scancode::F4),
+                key(Action::ToggleHud, Scancode::F1),
+                key(Action::CycleDynamicResolution, Scancode::F3),
+                key(Action::ToggleFullscreen, Scancode::F11),
+                key(Action::Screenshot, Scancode::F12),
+                key(Action::CaptureHighQuality, Scancode::F10),
+                key(Action::ReloadInput, Scancode::F5),
+                key(Action::EditMoveForward, Scancode::Up),
+                key(Action::EditMoveBack, Scancode::Down),
+                key(Action::EditMoveLeft, Scancode::Left),
+                key(Action::EditMoveRight, Scancode::Right),
+                key(Action::EditMoveUp, Scancode::PageUp),
+                key(Action::EditMoveDown, Scancode::PageDown),
+                key(Action::EditScaleUp, Scancode::Equals),
+                (Action::EditScaleUp, Binding::WheelUp),
+                key(Action::EditScaleDown, Scancode::Minus),
+                (Action::EditScaleDown, Binding::WheelDown),
+                key(Action::EditCycleMaterial, Scancode::M),
+                key(Action::EditDuplicate, Scancode::Insert),
+                key(Action::EditDelete, Scancode::Delete),
+                key(Action::EditDelete, Scancode::Backspace),
+                key(Action::SaveScene, Scancode::F2),
+                pad(Action::RollLeft, Button::LeftShoulder),
+                pad(Action::RollRight, Button::RightShoulder),
+                pad(Action::Sprint, Button::LeftStick),
+                pad(Action::Slow, Button::RightStick),
+                pad(Action::Pick, Button::A),
+                pad(Action::CycleStereo, Button::B),
+                pad(Action::ToggleHeatmap, Button::X),
+                pad(Action::CycleProjection, Button::Y),
+                pad(Action::ToggleEditMode, Button::Back),
+                pad(Action::TogglePhysicalLens, Button::Start),
+                pad(Action::OpenAperture, Button::DPadUp),
+                pad(Action::CloseAperture, Button::DPadDown),
+                pad(Action::CycleAperture, Button::DPadRight),
+                pad(Action::CycleSampler, Button::DPadLeft),
+            ],
+            sensitivity: 0.01,
+            look_speed: 1.5,
+            stick_deadzone: 0.15,
+            trigger_deadzone: 0.05,
+            stick_exponent: 2_f64,
+            invert_x: false,
+            invert_y: false,
+        }
+    }
+
+    pub fn load(path: &str) -> Result<Self, String> {
+        let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+        let mut config = Self::default_config();
+        let mut rebound: Vec<Action> = Vec::new();
+
+        for (line_no, line) in text.lines().enumerate() {
+            let err = |e: String| format!("{} line {}: {}", path, line_no + 1, e);
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            match parts.first() {
+                None => {}
+                Some(p) if p.starts_with('#') => {}
+                Some(&"bind") if parts.len() > 2 => {
+                    let action = ACTION_NAMES
+                        .iter()
+                        .find(|(_, name)| *name == parts[1])
+                        .map(|(action, _)| *action)
+                        .ok_or_else(|| err(format!("unknown action '{}'", parts[1])))?;
+                    let binding = Binding::parse(&parts[2..].join(" ")).map_err(err)?;
+                    //the first time an action shows up its defaults go away
+                    if !rebound.contains(&action) {
+                        config.bindings.retain(|(a, _)| *a != action);
+                        rebound.push(action);
+                    }
+                    config.bindings.push((action, binding));
+                }
+                Some(&"sensitivity")
+                | Some(&"look_speed")
+                | Some(&"stick_deadzone")
+                | Some(&"trigger_deadzone")
+                | Some(&"stick_exponent")
+                    if parts.len() == 2 =>
+                {
+                    let value = parts[1].parse::<f64>().map_err(|e| err(e.to_string()))?;
+                    match parts[0] {
+                        "sensitivity" => config.sensitivity = value,
+                        "look_speed" => config.look_speed = value,
+                        "stick_deadzone" => config.stick_deadzone = value.clamp(0_f64, 0.99),
+                        "trigger_deadzone" => config.trigger_deadzone = value.clamp(0_f64, 0.99),
+                        _ => config.stick_exponent = value.max(0.1),
+                    }
+                }
+                Some(&"invert_x") | Some(&"invert_y") if parts.len() == 2 => {
+                    let value = parts[1].parse::<bool>().map_err(|e| err(e.to_string()))?;
+                    match parts[0] {
+                        "invert_x" => config.invert_x = value,
+                        _ => config.invert_y = value,
+                    }
+                }
+                _ => return Err(err(format!("can't parse '{}'", line))),
+            }
+        }
+        Ok(config)
+    }
+
+    //falls back to the defaults when the file is missing or broken
+    pub fn load_or_default(path: &str) -> Self {
+        match Self::load(path) {
+            Ok(config) => {
+                println!("loaded input config {}", path);
+                config
+            }
+            Err(e) => {
+                println!("using default input config ({})", e);
+                Self::default_config()
+            }
+        }
+    }
+
+    pub fn actions_for(&self, binding: Binding) -> Vec<Action> {
+        self.bindings
+            .iter()
+            .filter(|(_, b)| *b == binding)
+            .map(|(action, _)| *action)
+            .collect()
+    }
+
+    pub fn is_held(&self, action: Action, held: &HeldInputs) -> bool {
+        self.bindings.iter().any(|(a, binding)| {
+            *a == action
+                && match *binding {
+                    Binding::Key(scancode) => held.keys.is_scancode_pressed(scancode),
+                    Binding::Mouse(button) => held.mouse.is_mouse_button_pressed(button),
+                    Binding::Pad(button) => held.pads.is_button_held(button),
+                    Binding::WheelUp | Binding::WheelDown => false, //never held, only notches
+                }
+        })
+    }
+
+    //keys and sticks are added together, so either can drive the camera
+    pub fn move_input(&self, held: &HeldInputs) -> MoveInput {
+        let axis = |pos: Action, neg: Action| {
+            self.is_held(pos, held) as i32 as f64 - self.is_held(neg, held) as i32 as f64
+        };
+        let (dz, exponent) = (self.stick_deadzone, self.stick_exponent);
+        let (stick_x, stick_y) = held.pads.stick(Axis::LeftX, Axis::LeftY, dz, exponent);
+        let rise = held
+            .pads
+            .trigger(Axis::TriggerRight, self.trigger_deadzone, exponent)
+            - held
+                .pads
+                .trigger(Axis::TriggerLeft, self.trigger_deadzone, exponent);
+        let clamp = |v: f64| v.clamp(-1_f64, 1_f64);
+        MoveInput {
+            forward: clamp(axis(Action::MoveForward, Action::MoveBack) - stick_y),
+            right: clamp(axis(Action::MoveRight, Action::MoveLeft) + stick_x),
+            up: clamp(axis(Action::MoveUp, Action::MoveDown) + rise),
+            roll: axis(Action::RollRight, Action::RollLeft),
+            sprint: self.is_held(Action::Sprint, held),
+            slow: self.is_held(Action::Slow, held),
+        }
+    }
+
+    //yaw and pitch from the look keys and the right stick over dt seconds
+    pub fn held_look(&self, held: &HeldInputs, dt: f64) -> (f64, f64) {
+        let axis = |pos: Action, neg: Action| {
+            self.is_held(pos, held) as i32 as f64 - self.is_held(neg, held) as i32 as f64
+        };
+        let (stick_x, stick_y) = held.pads.stick(
+            Axis::RightX,
+            Axis::RightY,
+            self.stick_deadzone,
+            self.stick_exponent,
+        );
+        let yaw = (axis(Action::LookRight, Action::LookLeft) + stick_x).clamp(-1_f64, 1_f64);
+        let pitch = (axis(Action::LookUp, Action::LookDown) - stick_y).clamp(-1_f64, 1_f64);
+        self.apply_inversion(yaw * self.look_speed * dt, pitch * self.look_speed * dt)
+    }
+
+    //yaw and pitch from relative mouse motion
+    pub fn mouse_look(&self, xrel: i32, yrel: i32) -> (f64, f64) {
+        //screen y grows downward, so moving the mouse up pitches up
+        self.apply_inversion(
+            xrel as f64 * self.sensitivity,
+            -yrel as f64 * self.sensitivity,
+        )
+    }
+
+    fn apply_inversion(&self, yaw: f64, pitch: f64) -> (f64, f64) {
+        let flip = |v: f64, invert: bool| if invert { -v } else { v };
+        (flip(yaw, self.invert_x), flip(pitch, self.invert_y))
+    }
+}
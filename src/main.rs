@@ -1,37 +1,248 @@
+mod animation;
+mod aperture;
 mod camera;
+mod capture;
 mod color;
+mod display;
+mod editor;
+mod filter;
+mod gamepad;
 mod helper;
+mod hud;
+mod image_io;
+mod input;
 mod interval;
 mod material;
+mod movement;
+mod physics;
+mod plane;
+mod quat;
 mod ray;
+mod render_worker;
+mod resolution;
+mod sampler;
+mod scene;
 mod sphere;
+mod temporal;
 mod vec3;
 
 extern crate sdl2;
-use camera::Camera;
+use animation::CameraPath;
+use aperture::{Aperture, ApertureMask};
+use camera::{Camera, PhysicalLens, StereoMode};
+use capture::Capture;
 use color::Color;
-use material::{Dielectric, Lambertian, Metal};
+use display::Display;
+use editor::Editor;
+use filter::Filter;
+use gamepad::Gamepads;
+use helper::rad_to_deg;
+use hud::Hud;
+use input::{Action, Binding, HeldInputs, InputConfig};
+use material::Metal;
+use movement::{FlyController, WalkController};
+use physics::Physics;
 use ray::HittableList;
+use render_worker::{FrameStats, RenderJob, RenderWorker};
+use resolution::DynamicResolution;
+use sampler::SamplerKind;
+use scene::{Ball, MaterialDesc, Scene};
+use sdl2::event::WindowEvent;
 use sdl2::pixels::PixelFormatEnum;
+use sdl2::video::FullscreenType;
 use sphere::Sphere;
 use std::error::Error;
 use std::io::{BufRead, BufReader, Write};
 use std::net::TcpStream;
-use std::rc::Rc;
 use std::sync::mpsc::channel;
+use std::sync::Arc;
 use std::thread;
 use vec3::Point3;
 use vec3::Vec3;
 
-const IMG_WIDTH: u32 = 200;
-const IMG_HEIGHT: u32 = 112;
+//starting window size, the render resolution is the window size over PIXEL_SCALE
+const WINDOW_WIDTH: u32 = 1000;
+const WINDOW_HEIGHT: u32 = 560;
 const PIXEL_SCALE: u32 = 5;
-const MOVEMENT_SCALE: f64 = 0.5_f64;
+const TARGET_FRAME_S: f64 = 1_f64 / 30_f64; //what dynamic resolution aims for while moving
+const CAPTURE_SAMPLES: i32 = 256; //samples per pixel for high quality captures
+const NOTICE_S: f64 = 3_f64; //how long messages stay on screen
+const INPUT_CONFIG_PATH: &str = "input.cfg";
+const BALL_RADIUS: f64 = 0.3; //of thrown balls
+const THROW_SPEED: f64 = 6_f64; //units per second, along the view direction
 
+//the world is always rebuilt from the editable scene, with the other player's sphere (if
+//they're connected) appended after the scene's objects
+fn build_world(scene: &Scene, other_player: Option<Point3>) -> HittableList {
+    let mut world = scene.to_world();
+    if let Some(pos) = other_player {
+        add_player(&mut world, pos);
+    }
+    world
+}
+
+fn add_player(world: &mut HittableList, pos: Point3) {
+    let player_material = Arc::new(Metal::new(Color::new(0.01, 0.2, 0.3), 0.0));
+    world.add(Arc::new(Sphere::new(pos, 0.5, player_material)));
+}
+
+fn build_camera(display: &Display) -> Camera {
+    //aspect ratio, img_width, samples_per_pixel, depth, vertical angle fov
+    let mut cam: Camera = Camera::new(
+        display.aspect_ratio(),
+        display.render_width(),
+        10,
+        100,
+        20_f64,
+    );
+
+    cam.look_at(
+        Point3::new(13.0, 2.0, 3.0),
+        Point3::new(0.0, 0.0, 0.0),
+        Vec3::new(0.0, 1.0, 0.0),
+    );
+    cam.defocus_angle = 0.6;
+    cam.focus_dist = 10.0;
+    cam.convergence_dist = 10.0;
+    cam.interocular = 0.3; //exaggerated so the depth reads at this scene's scale
+    cam.sampler = SamplerKind::Sobol;
+    cam.max_samples_per_pixel = 40;
+    cam.adaptive_threshold = 0.05;
+    cam
+}
+
+//autofocus: set focus_dist to whatever is under pixel (x, y), left alone for empty sky
+fn focus_on_pixel(cam: &mut Camera, world: &HittableList, x: i32, y: i32) {
+    if let Some(pick) = cam.pick(world, x, y) {
+        println!(
+            "focus on object {} at {:.2} (depth {:.2})",
+            pick.object_id, pick.distance, pick.depth
+        );
+        cam.focus_dist = pick.depth;
+    }
+}
+
+//what the HUD shows, frame_s is how long the event loop took for the last frame and stats
+//describe the newest rendered image, at its size before any upscaling
+fn hud_lines(
+    cam: &Camera,
+    frame_s: f64,
+    stats: &FrameStats,
+    other_player: Option<Point3>,
+) -> Vec<String> {
+    let FrameStats {
+        size: (width, height),
+        render_s,
+        rays,
+        samples,
+        ..
+    } = *stats;
+    let (yaw, pitch, roll) = cam.yaw_pitch_roll();
+    let p = cam.lookfrom;
+    let players = match other_player {
+        Some(o) => format!(
+            "players: 2 (other at {:.2} {:.2} {:.2})",
+            o.x(),
+            o.y(),
+            o.z()
+        ),
+        None => "players: 1".to_string(),
+    };
+    vec![
+        format!(
+            "frame {:.1} ms ({:.0} fps)  render {:.1} ms at {}x{}",
+            frame_s * 1000_f64,
+            1_f64 / frame_s.max(1e-6),
+            render_s * 1000_f64,
+            width,
+            height
+        ),
+        format!(
+            "{:.2} mrays/s  {} samples ({:.1} per pixel)",
+            rays as f64 / render_s.max(1e-6) / 1e6,
+            samples,
+            samples as f64 / (width * height).max(1) as f64
+        ),
+        format!("pos {:.2} {:.2} {:.2}", p.x(), p.y(), p.z()),
+        format!(
+            "yaw {:.1}  pitch {:.1}  roll {:.1}",
+            rad_to_deg(yaw),
+            rad_to_deg(pitch),
+            rad_to_deg(roll)
+        ),
+        format!("fov {:.1}  focus {:.2}", cam.vfov(), cam.focus_dist),
+        players,
+    ]
+}
+
+//seconds since the epoch, to give saved files unique names
+fn timestamp() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+fn load_scene(path: Option<&String>) -> Result<Scene, String> {
+    match path {
+        Some(path) => Scene::load(path),
+        None => Ok(Scene::default_scene()),
+    }
+}
+
+// batch mode: --animate <path file> <start> <end> <fps> <output prefix> [scene file]
+// renders every frame of the camera path between start and end (seconds) as a numbered image
+fn render_animation(args: &[String]) -> Result<(), Box<dyn Error>> {
+    let usage = "usage: --animate <path file> <start> <end> <fps> <output prefix> [scene file]";
+    if args.len() != 5 && args.len() != 6 {
+        return Err(usage.into());
+    }
+    let path = CameraPath::load(&args[0])?;
+    let start: f64 = args[1].parse()?;
+    let end: f64 = args[2].parse()?;
+    let fps: f64 = args[3].parse()?;
+    let prefix = &args[4];
+    if !(start.is_finite() && end.is_finite() && fps.is_finite()) || fps <= 0_f64 {
+        return Err(format!(
+            "{}\nstart, end and fps have to be numbers, fps above 0",
+            usage
+        )
+        .into());
+    }
+    if end < start {
+        return Err(format!("{}\nend ({}) is before start ({})", usage, end, start).into());
+    }
+
+    let world = build_world(&load_scene(args.get(5))?, None);
+    let mut cam = build_camera(&Display::new(WINDOW_WIDTH, WINDOW_HEIGHT, PIXEL_SCALE));
+    let (width, height) = cam.dimensions();
+    let frame_count = ((end - start) * fps).floor() as i64 + 1;
+    for frame in 0..frame_count {
+        let time = start + frame as f64 / fps;
+        path.apply(time, &mut cam);
+        let image = cam.render(&world);
+        let file_name = format!("{}_{:04}.ppm", prefix, frame);
+        image_io::write_ppm(&file_name, width as u32, height as u32, &image)?;
+        println!(
+            "frame {}/{} (t = {:.3}s) -> {}",
+            frame + 1,
+            frame_count,
+            time,
+            file_name
+        );
+    }
+    Ok(())
+}
+
+//usage: <port> [scene file], or --animate ... for batch rendering
 //probably should change to udp rather than tcp
 fn main() -> Result<(), Box<dyn Error>> {
     let args: Vec<String> = std::env::args().collect();
+    if args.get(1).map(|a| a.as_str()) == Some("--animate") {
+        return render_animation(&args[2..]);
+    }
     let port: String = args[1].to_owned().trim().to_string();
+    let mut scene = load_scene(args.get(2))?;
+    let mut editor = Editor::new(args.get(2).map_or("scene.txt", |p| p.as_str()));
 
     //our data
     let (tx_server, rx_server) = channel::<(f64, f64, f64)>();
@@ -39,8 +250,8 @@ fn main() -> Result<(), Box<dyn Error>> {
     //other player/client data
     let (tx_pos_update, rx_pos_update) = channel::<(f64, f64, f64)>();
 
-    //other player flag
-    let mut other_player_set = false;
+    //other player's position, once we've heard from them
+    let mut other_player: Option<Point3> = None;
 
     thread::spawn(move || {
         if let Ok(mut stream) = TcpStream::connect("127.0.0.1:".to_owned() + port.as_str()) {
@@ -81,106 +292,83 @@ fn main() -> Result<(), Box<dyn Error>> {
 
     //sdl initialization code
     let sdl_context = sdl2::init()?;
+    let mut display = Display::new(WINDOW_WIDTH, WINDOW_HEIGHT, PIXEL_SCALE);
     let video_subsystem = sdl_context.video()?;
     sdl_context.mouse().set_relative_mouse_mode(true);
     let window = video_subsystem
         .window(
             "raytracing in one weekend real time",
-            IMG_WIDTH * PIXEL_SCALE,
-            IMG_HEIGHT * PIXEL_SCALE,
+            display.window_width,
+            display.window_height,
         )
         .position_centered()
+        .resizable()
         .build()
         .map(|e| e.to_owned())?;
     let mut canvas = window.into_canvas().build().map_err(|e| e.to_owned())?;
     let mut event_pump = sdl_context.event_pump()?;
+    let mut gamepads = Gamepads::new(sdl_context.game_controller()?);
 
     let frame_duration = std::time::Duration::from_millis(20);
 
-    //map init code
-    let mut world: HittableList = HittableList::new();
-    let material_ground = Rc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
-
-    world.add(Rc::new(Sphere::new(
-        Point3::new(0.0, -1000_f64, 0.0),
-        1000.0,
-        material_ground,
-    )));
-    let material1 = Rc::new(Dielectric::new(1.5));
-    world.add(Rc::new(Sphere::new(
-        Point3::new(0_f64, 1_f64, 0_f64),
-        1.0,
-        material1,
-    )));
-    let material2 = Rc::new(Lambertian::new(Color::new(1.0_f64, 0.1_f64, 0.1)));
-    world.add(Rc::new(Sphere::new(
-        Point3::new(-3_f64, 1_f64, 0_f64),
-        1.0,
-        material2,
-    )));
-    let material3 = Rc::new(Metal::new(Color::new(0.01, 0.01, 0.3), 0.0));
-    world.add(Rc::new(Sphere::new(
-        Point3::new(3_f64, 1_f64, 0_f64),
-        1.0,
-        material3,
-    )));
-
-    //aspect ratio, img_width, samples_per_pixel, depth, vertical angle fov
-    let mut cam: Camera = Camera::new(16_f64 / 9_f64, 200, 10, 40, 20_f64);
-
-    cam.lookfrom = Point3::new(13.0, 2.0, 3.0);
-    cam.lookat = Point3::new(0.0, 0.0, 0.0);
-    cam.vup = Vec3::new(0.0, 1.0, 0.0);
-    cam.defocus_angle = 0.6;
-    cam.focus_dist = 10.0;
+    let mut world = Arc::new(build_world(&scene, other_player));
+    let mut cam = build_camera(&display);
 
-    //initial rendering code
-    let mut image_vector: Vec<u8> = cam.render(&world);
+    //the window starts black until the worker's first frame arrives
+    let (img_width, img_height) = cam.dimensions();
+    let mut image_vector: Vec<u8> = vec![0; (img_width * img_height * 3) as usize];
     let texture_creator = canvas.texture_creator();
     let mut texture = texture_creator
-        .create_texture_streaming(PixelFormatEnum::RGB24, IMG_WIDTH, IMG_HEIGHT)
+        .create_texture_streaming(PixelFormatEnum::RGB24, img_width as u32, img_height as u32)
         .map_err(|e| e.to_owned())?;
+    texture.update(None, &image_vector, (img_width * 3) as usize)?;
 
-    // upload image data to texture
-    texture.update(None, &image_vector, (IMG_WIDTH * 3) as usize)?;
-    canvas.clear();
-    canvas
-        .copy(&texture, None, None)
-        .map_err(|e| e.to_string())?;
-    canvas.present();
-
-    let mut rerender_flag: bool = false;
+    let mut worker = RenderWorker::spawn();
+    let mut world_changed = true; //the worker hasn't got a world yet
+    let mut balls_changed = false; //the world changed, but only because balls moved
+    let mut rerender_flag: bool = true;
+    let mut present_flag: bool = true;
     let mut mouse_lock: bool = false;
+    let mut show_heatmap: bool = false;
+    let mut temporal: bool = true;
+    let mut fly = FlyController::new();
+    let mut walk: Option<WalkController> = None; //walking with collisions instead of flying
+    let mut hud = Hud::new();
+    let mut dynamic_res = DynamicResolution::new(TARGET_FRAME_S);
+    let mut physics = Physics::new();
+    let mut ball_material = MaterialDesc::Lambertian(Color::new(0.5, 0.5, 0.5));
+    let mut input = InputConfig::load_or_default(INPUT_CONFIG_PATH);
+    let mut last_frame = std::time::Instant::now();
+    let mut capture: Option<Capture> = None;
+    let mut notice: Option<(String, std::time::Instant)> = None;
+    //the newest frame the worker finished, for the HUD
+    let mut last_stats = FrameStats {
+        size: (img_width, img_height),
+        render_s: 0_f64,
+        rays: 0,
+        samples: 0,
+        reduced: false,
+        complete: false,
+    };
 
     'running: loop {
         let frame_start = std::time::Instant::now();
+        //clamped so a slow render doesn't teleport the camera on the next frame
+        let dt = (frame_start - last_frame).as_secs_f64().min(0.25);
+        last_frame = frame_start;
+
+        //key and mouse presses get translated into actions through the input config and run
+        //after polling, together with where the cursor was for the ones that care
+        let mut triggered: Vec<(Action, Option<(i32, i32)>)> = Vec::new();
+        let mut camera_moved = false;
         for event in event_pump.poll_iter() {
             match event {
-                sdl2::event::Event::Quit { .. }
-                | sdl2::event::Event::KeyDown {
-                    keycode: Some(sdl2::keyboard::Keycode::Escape),
-                    ..
-                } => break 'running,
-                sdl2::event::Event::KeyDown { keycode, .. } => {
-                    match keycode {
-                        // Move the camera with arrow keys
-                        Some(sdl2::keyboard::Keycode::W) => {
-                            cam.move_fwd(MOVEMENT_SCALE);
-                        }
-                        Some(sdl2::keyboard::Keycode::A) => {
-                            cam.move_left(MOVEMENT_SCALE);
-                        }
-                        Some(sdl2::keyboard::Keycode::S) => {
-                            cam.move_backward(MOVEMENT_SCALE);
-                        }
-                        Some(sdl2::keyboard::Keycode::D) => {
-                            cam.move_right(MOVEMENT_SCALE);
+                sdl2::event::Event::Quit { .. } => break 'running,
+                sdl2::event::Event::KeyDown { scancode, .. } => {
+                    if let Some(scancode) = scancode {
+                        for action in input.actions_for(Binding::Key(scancode)) {
+                            triggered.push((action, None));
                         }
-                        Some(sdl2::keyboard::Keycode::L) => {
-                            mouse_lock = !mouse_lock;
-                            sdl_context.mouse().set_relative_mouse_mode(mouse_lock);
-                        }
-                        _ => {}
                     }
                     rerender_flag = true;
                     match tx_pos_update.send((cam.lookfrom.x(), cam.lookfrom.y(), cam.lookfrom.z()))
@@ -193,49 +381,390 @@ fn main() -> Result<(), Box<dyn Error>> {
                         }
                     }
                 }
+                sdl2::event::Event::MouseButtonDown {
+                    mouse_btn, x, y, ..
+                } => {
+                    for action in input.actions_for(Binding::Mouse(mouse_btn)) {
+                        triggered.push((action, Some((x, y))));
+                    }
+                }
+                sdl2::event::Event::MouseWheel { y, .. } => {
+                    let notch = match y > 0 {
+                        true => Binding::WheelUp,
+                        false => Binding::WheelDown,
+                    };
+                    for _ in 0..y.abs() {
+                        for action in input.actions_for(notch) {
+                            triggered.push((action, None));
+                        }
+                    }
+                }
+                sdl2::event::Event::ControllerButtonDown { button, .. } => {
+                    for action in input.actions_for(Binding::Pad(button)) {
+                        triggered.push((action, None));
+                    }
+                }
+                sdl2::event::Event::ControllerDeviceAdded { .. }
+                | sdl2::event::Event::ControllerDeviceRemoved { .. } => {
+                    gamepads.handle_event(&event);
+                }
+                sdl2::event::Event::Window {
+                    win_event: WindowEvent::SizeChanged(width, height),
+                    ..
+                } => {
+                    display.resize(width, height, &mut cam);
+                    rerender_flag = true;
+                }
                 sdl2::event::Event::MouseMotion { xrel, yrel, .. } => {
-                    let sensitivity = 0.01; //adjust this for faster/slower rotation
-                    let yaw = xrel as f64 * sensitivity;
-                    let pitch = -yrel as f64 * sensitivity; //invert Y so up is up
+                    let (yaw, pitch) = input.mouse_look(xrel, yrel);
                     cam.adjust_view(yaw, pitch);
-                    rerender_flag = true;
+                    camera_moved = true;
                 }
                 _ => {}
             }
         }
 
+        for (action, cursor) in triggered {
+            match action {
+                Action::Quit => break 'running,
+                Action::ReloadInput => input = InputConfig::load_or_default(INPUT_CONFIG_PATH),
+                Action::ToggleWalk => {
+                    walk = match walk {
+                        Some(_) => None,
+                        None => Some(WalkController::new(&cam)),
+                    };
+                    println!("walk mode: {}", walk.is_some());
+                }
+                Action::ToggleMouseLock => {
+                    mouse_lock = !mouse_lock;
+                    sdl_context.mouse().set_relative_mouse_mode(mouse_lock);
+                }
+                Action::Pick => {
+                    //with the mouse captured there's no cursor, so use the screen center
+                    let (img_width, img_height) = cam.dimensions();
+                    let (px, py) = match (cursor, sdl_context.mouse().relative_mouse_mode()) {
+                        (Some((x, y)), false) => display.to_render_pixel(x, y, &cam),
+                        _ => (img_width / 2, img_height / 2),
+                    };
+                    match editor.active {
+                        true => editor.select(cam.pick(world.as_ref(), px, py), &scene),
+                        false => focus_on_pixel(&mut cam, &world, px, py),
+                    }
+                }
+                Action::FocusCenter => {
+                    let (img_width, img_height) = cam.dimensions();
+                    focus_on_pixel(&mut cam, &world, img_width / 2, img_height / 2);
+                }
+                Action::ToggleFullscreen => {
+                    display.fullscreen = !display.fullscreen;
+                    let mode = match display.fullscreen {
+                        true => FullscreenType::Desktop,
+                        false => FullscreenType::Off,
+                    };
+                    //the resulting size change arrives as a window event
+                    canvas.window_mut().set_fullscreen(mode)?;
+                }
+                Action::EditMoveForward
+                | Action::EditMoveBack
+                | Action::EditMoveLeft
+                | Action::EditMoveRight
+                | Action::EditMoveUp
+                | Action::EditMoveDown
+                | Action::EditScaleUp
+                | Action::EditScaleDown
+                | Action::EditCycleMaterial
+                | Action::EditDuplicate
+                | Action::EditDelete
+                | Action::SaveScene => {
+                    let changed = editor.active && editor.handle_action(action, &mut scene, &cam);
+                    if changed {
+                        //a ball may have been resting on what just changed
+                        physics.wake_all(&mut scene);
+                        world = Arc::new(build_world(&scene, other_player));
+                        world_changed = true;
+                    }
+                }
+                Action::ToggleEditMode => {
+                    editor.active = !editor.active;
+                    println!("edit mode: {}", editor.active);
+                }
+                Action::CycleSampler => {
+                    cam.sampler = cam.sampler.next();
+                    println!("sampler: {:?}", cam.sampler);
+                }
+                Action::CycleFilter => {
+                    let kind = cam.filter.kind.next();
+                    cam.filter = Filter::new(kind, kind.default_radius());
+                    println!("filter: {:?} radius {}", kind, cam.filter.radius);
+                }
+                Action::CycleProjection => {
+                    cam.projection = cam.projection.next();
+                    println!("projection: {:?}", cam.projection);
+                }
+                Action::CycleStereo => {
+                    cam.stereo = cam.stereo.next();
+                    println!("stereo: {:?}", cam.stereo);
+                }
+                Action::CycleDebugView => {
+                    cam.debug_view = cam.debug_view.next();
+                    println!("debug view: {:?}", cam.debug_view);
+                }
+                Action::SaveCubemap => {
+                    let face_size = cam.dimensions().1;
+                    let strip = cam.render_cubemap(world.as_ref(), face_size);
+                    match image_io::write_ppm(
+                        "cubemap.ppm",
+                        (face_size * 6) as u32,
+                        face_size as u32,
+                        &strip,
+                    ) {
+                        Ok(_) => println!("saved cubemap.ppm"),
+                        Err(e) => println!("couldn't save cubemap: {}", e),
+                    }
+                }
+                Action::TogglePhysicalLens => {
+                    //70mm f/2.8 at 1/3200s ISO 100 exposes about like the default
+                    cam.physical = match cam.physical {
+                        Some(_) => None,
+                        None => Some(PhysicalLens::new(70_f64, 2.8, 1_f64 / 3200_f64, 100_f64)),
+                    };
+                    println!("physical lens: {:?}", cam.physical);
+                }
+                Action::OpenAperture => {
+                    if let Some(lens) = cam.physical.as_mut() {
+                        lens.step_f_stop(-1_f64);
+                        println!("f/{:.1}", lens.f_number);
+                    }
+                }
+                Action::CloseAperture => {
+                    if let Some(lens) = cam.physical.as_mut() {
+                        lens.step_f_stop(1_f64);
+                        println!("f/{:.1}", lens.f_number);
+                    }
+                }
+                Action::CycleAperture => {
+                    //circle -> hexagon -> rotated pentagon -> aperture.pgm mask, if any
+                    cam.aperture = match cam.aperture {
+                        Aperture::Circle => Aperture::Polygon {
+                            blades: 6,
+                            rotation: 0_f64,
+                        },
+                        Aperture::Polygon { blades: 6, .. } => Aperture::Polygon {
+                            blades: 5,
+                            rotation: 0.3,
+                        },
+                        Aperture::Polygon { .. } => match ApertureMask::load("aperture.pgm") {
+                            Ok(mask) => Aperture::Mask(Arc::new(mask)),
+                            Err(e) => {
+                                println!("no aperture mask: {}", e);
+                                Aperture::Circle
+                            }
+                        },
+                        Aperture::Mask(_) => Aperture::Circle,
+                    };
+                }
+                Action::ThrowBall => {
+                    //starts a little in front of the camera so it doesn't clip the near plane
+                    let forward = cam.forward();
+                    ball_material = ball_material.next_in_palette();
+                    scene.balls.push(Ball {
+                        center: cam.lookfrom + (2_f64 * BALL_RADIUS + 0.5) * forward,
+                        radius: BALL_RADIUS,
+                        material: ball_material,
+                        velocity: THROW_SPEED * forward,
+                        asleep: false,
+                    });
+                    world = Arc::new(build_world(&scene, other_player));
+                    world_changed = true;
+                }
+                Action::ToggleHeatmap => {
+                    show_heatmap = !show_heatmap;
+                }
+                Action::ToggleTemporal => {
+                    temporal = !temporal;
+                    println!("temporal reprojection: {}", temporal);
+                }
+                Action::ToggleHud => hud.visible = !hud.visible,
+                Action::Screenshot => {
+                    let file_name = format!("screenshot_{}.png", timestamp());
+                    let query = texture.query();
+                    let (width, height) = (query.width, query.height);
+                    let message =
+                        match image_io::write_png(&file_name, width, height, &image_vector) {
+                            Ok(_) => format!("saved {}", file_name),
+                            Err(e) => format!("couldn't save screenshot: {}", e),
+                        };
+                    println!("{}", message);
+                    notice = Some((message, std::time::Instant::now()));
+                }
+                Action::CaptureHighQuality => match capture {
+                    Some(_) => println!("a capture is already running"),
+                    None => {
+                        //full window resolution, same framing
+                        let mut hq_cam = cam.clone();
+                        hq_cam.set_width(display.window_width as i32);
+                        hq_cam.set_samples_per_pixel(CAPTURE_SAMPLES);
+                        hq_cam.max_samples_per_pixel =
+                            hq_cam.max_samples_per_pixel.max(CAPTURE_SAMPLES);
+                        let prefix = format!("capture_{}", timestamp());
+                        capture = Some(Capture::start(hq_cam, &prefix, world.clone()));
+                    }
+                },
+                Action::CycleDynamicResolution => {
+                    dynamic_res.mode = dynamic_res.mode.next();
+                    println!("dynamic resolution: {:?}", dynamic_res.mode);
+                }
+                //held actions are read from the keyboard state below
+                _ => {}
+            }
+            rerender_flag = true;
+        }
+
+        //movement reads the held keys rather than key events, so it runs at a steady speed
+        //independent of key repeat and works for several keys at once
+        let held = HeldInputs {
+            keys: event_pump.keyboard_state(),
+            mouse: event_pump.mouse_state(),
+            pads: &gamepads,
+        };
+        let (yaw, pitch) = input.held_look(&held, dt);
+        if yaw != 0_f64 || pitch != 0_f64 {
+            cam.adjust_view(yaw, pitch);
+            camera_moved = true;
+        }
+        let move_input = input.move_input(&held);
+        let moved = match walk.as_mut() {
+            Some(walker) => walker.update(&mut cam, &move_input, world.as_ref(), dt),
+            None => fly.update(&mut cam, &move_input, dt),
+        };
+        if moved {
+            camera_moved = true;
+            let _ = tx_pos_update.send((cam.lookfrom.x(), cam.lookfrom.y(), cam.lookfrom.z()));
+        }
+        //moving balls count as motion too, so they get the cheaper frames while they fly
+        let balls_moved = physics.update(&mut scene, dt);
+        if balls_moved {
+            world = Arc::new(build_world(&scene, other_player));
+            balls_changed = true;
+        }
+        let moving = camera_moved || balls_moved;
+        rerender_flag |= moving || dynamic_res.needs_full_render(moving);
+
         if let Ok((x, y, z)) = rx_server.try_recv() {
             rerender_flag = true;
             println!("other player coords: {}, {}, {}", x, y, z);
-            if !other_player_set {
-                other_player_set = true;
-
-                let player_material = Rc::new(Metal::new(Color::new(0.01, 0.2, 0.3), 0.0));
-                world.add(Rc::new(Sphere::new(
-                    Point3::new(x, y, z),
-                    0.5,
-                    player_material,
-                )));
-            } else {
-                world.drop_last();
-                let player_material = Rc::new(Metal::new(Color::new(0.01, 0.2, 0.3), 0.0));
-                world.add(Rc::new(Sphere::new(
-                    Point3::new(x, y, z),
-                    0.5,
-                    player_material,
-                )));
-            }
+            other_player = Some(Point3::new(x, y, z));
+            world = Arc::new(build_world(&scene, other_player));
+            world_changed = true;
         }
 
+        //hand the view to the worker, which drops whatever it was still rendering. A still
+        //camera gets a quick one sample preview first so changes show up right away
         if rerender_flag {
-            image_vector = cam.render(&world);
-            texture.update(None, &image_vector, (IMG_WIDTH * 3) as usize)?;
+            let (frame_cam, reduced) = dynamic_res.frame_camera(&cam, moving);
+            let mut passes = Vec::new();
+            if !reduced && frame_cam.samples_per_pixel() > 1 {
+                let mut preview = frame_cam.clone();
+                preview.set_samples_per_pixel(1);
+                preview.max_samples_per_pixel = 1;
+                passes.push(preview);
+            }
+            passes.push(frame_cam);
+            let highlight = match editor.active && cam.stereo == StereoMode::Mono {
+                true => editor.selected,
+                false => None,
+            };
+            worker.submit(RenderJob {
+                passes,
+                world: (world_changed || balls_changed).then(|| world.clone()),
+                keep_history: !world_changed,
+                show_heatmap,
+                temporal,
+                highlight,
+                reduced,
+            });
+            world_changed = false;
+            balls_changed = false;
+            rerender_flag = false;
+        }
+
+        if let Some(frame) = worker.latest_frame() {
+            let stats = frame.stats;
+            let (img_width, img_height) = match stats.reduced {
+                true => cam.dimensions(),
+                false => stats.size,
+            };
+            image_vector = match stats.reduced {
+                true => dynamic_res.finish_frame(
+                    frame.image,
+                    stats.size,
+                    (img_width, img_height),
+                    stats.render_s,
+                ),
+                false => frame.image,
+            };
+
+            //the render size follows the window, so the texture does too
+            let query = texture.query();
+            if (query.width, query.height) != (img_width as u32, img_height as u32) {
+                texture = texture_creator
+                    .create_texture_streaming(
+                        PixelFormatEnum::RGB24,
+                        img_width as u32,
+                        img_height as u32,
+                    )
+                    .map_err(|e| e.to_owned())?;
+            }
+            texture.update(None, &image_vector, (img_width * 3) as usize)?;
+            last_stats = stats;
+            present_flag = true;
+        }
+
+        if let Some(result) = capture.as_mut().and_then(|c| c.poll()) {
+            let prefix = capture.take().map(|c| c.prefix).unwrap_or_default();
+            let message = match result {
+                Ok(_) => format!("saved {}.png and {}.exr", prefix, prefix),
+                Err(e) => format!("capture failed: {}", e),
+            };
+            println!("{}", message);
+            notice = Some((message, std::time::Instant::now()));
+        }
+        if notice
+            .as_ref()
+            .is_some_and(|(_, at)| at.elapsed().as_secs_f64() > NOTICE_S)
+        {
+            notice = None;
+            present_flag = true; //one more present to clear it
+        }
+
+        //capture progress and messages show even with the HUD hidden, and keep the window
+        //refreshing while they're up, as does a frame still rendering
+        let rendering = !last_stats.complete;
+        if present_flag || rendering || capture.is_some() || notice.is_some() {
+            let mut lines = match hud.visible {
+                true => hud_lines(&cam, dt, &last_stats, other_player),
+                false => Vec::new(),
+            };
+            if hud.visible && rendering {
+                lines.push(format!("rendering: {:.0}%", worker.progress() * 100_f64));
+            }
+            if let Some(c) = &capture {
+                lines.push(format!(
+                    "capturing {}: {:.0}%",
+                    c.prefix,
+                    c.progress() * 100_f64
+                ));
+            }
+            if let Some((message, _)) = &notice {
+                lines.push(message.clone());
+            }
             canvas.clear();
             canvas
                 .copy(&texture, None, None)
                 .map_err(|e| e.to_string())?;
+            hud.draw(&mut canvas, &lines)?;
             canvas.present();
-            rerender_flag = false;
+            present_flag = false;
         }
 
         let elapsed = frame_start.elapsed();
@@ -1,8 +1,8 @@
 use crate::{
     color::Color,
-    helper::random_f64,
     ray::{HitRecord, Ray, Scatter},
-    vec3::{dot, random_unit_vector, reflect, refract, unit_vector},
+    sampler::Sampler,
+    vec3::{dot, reflect, refract, sample_unit_vector, unit_vector},
 };
 
 pub struct Lambertian {
@@ -18,12 +18,13 @@ impl Lambertian {
 impl Scatter for Lambertian {
     fn scatter(
         &self,
-        ray_in: &Ray,
+        _ray_in: &Ray,
         hit_rec: &HitRecord,
         attenuation: &mut Color,
         scattered_ray: &mut Ray,
+        sampler: &mut dyn Sampler,
     ) -> bool {
-        let mut scatter_direction = hit_rec.normal + random_unit_vector();
+        let mut scatter_direction = hit_rec.normal + sample_unit_vector(sampler.get_2d());
 
         if scatter_direction.near_zero() {
             scatter_direction = hit_rec.normal;
@@ -32,6 +33,10 @@ impl Scatter for Lambertian {
         *attenuation = self.albedo;
         true
     }
+
+    fn albedo(&self) -> Color {
+        self.albedo
+    }
 }
 
 pub struct Metal {
@@ -56,13 +61,18 @@ impl Scatter for Metal {
         hit_rec: &HitRecord,
         attenuation: &mut Color,
         scattered_ray: &mut Ray,
+        sampler: &mut dyn Sampler,
     ) -> bool {
         let mut reflected = reflect(&ray_in.dir(), &hit_rec.normal);
-        reflected = unit_vector(&reflected) + (self.fuzz * random_unit_vector());
+        reflected = unit_vector(&reflected) + (self.fuzz * sample_unit_vector(sampler.get_2d()));
         *scattered_ray = Ray::new(hit_rec.p, reflected);
         *attenuation = self.albedo;
         dot(&scattered_ray.dir(), &hit_rec.normal) > 0_f64
     }
+
+    fn albedo(&self) -> Color {
+        self.albedo
+    }
 }
 
 pub struct Dielectric {
@@ -88,6 +98,7 @@ impl Scatter for Dielectric {
         hit_rec: &HitRecord,
         attenuation: &mut Color,
         scattered_ray: &mut Ray,
+        sampler: &mut dyn Sampler,
     ) -> bool {
         *attenuation = Color::new(1.0, 1.0, 1.0);
         let ri = if hit_rec.front_face {
@@ -101,7 +112,7 @@ impl Scatter for Dielectric {
 
         let direction = match (
             (ri * sin_theta > 1.0),
-            self.reflectance(cos_theta) > random_f64(),
+            self.reflectance(cos_theta) > sampler.get_1d(),
         ) {
             (true, _) | (_, true) => reflect(&unit_dir, &hit_rec.normal),
             _ => refract(&unit_dir, &hit_rec.normal, ri),
@@ -110,4 +121,9 @@ impl Scatter for Dielectric {
 
         true
     }
+
+    //clear glass
+    fn albedo(&self) -> Color {
+        Color::new(1.0, 1.0, 1.0)
+    }
 }
@@ -0,0 +1,222 @@
+use crate::{
+    camera::Camera,
+    interval::Interval,
+    ray::{HitRecord, Hittable, Ray},
+    vec3::{cross, dot, unit_vector, Point3, Vec3},
+};
+
+//what the player is asking for this frame, each axis in [-1, 1]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MoveInput {
+    pub forward: f64,
+    pub right: f64,
+    pub up: f64,
+    pub roll: f64,
+    pub sprint: bool,
+    pub slow: bool,
+}
+
+// First person fly controls. Velocity eases toward whatever the input asks for instead of
+// jumping to it, and everything is scaled by the frame time so speed doesn't depend on the
+// frame rate or the OS key repeat.
+pub struct FlyController {
+    velocity: Vec3, //x camera right, y world up, z camera forward
+    roll_velocity: f64,
+    pub max_speed: f64,         //units per second
+    pub max_roll_speed: f64,    //radians per second
+    pub acceleration: f64,      //how quickly velocity catches up with the input, per second
+    pub sprint_multiplier: f64, //speed factors while sprint/slow are held
+    pub slow_multiplier: f64,
+}
+
+impl FlyController {
+    pub fn new() -> Self {
+        Self {
+            velocity: Vec3::new_empty(),
+            roll_velocity: 0_f64,
+            max_speed: 5_f64,
+            max_roll_speed: 1.5,
+            acceleration: 10_f64,
+            sprint_multiplier: 3_f64,
+            slow_multiplier: 0.25,
+        }
+    }
+
+    //advances the camera by dt seconds, true if it moved
+    pub fn update(&mut self, cam: &mut Camera, input: &MoveInput, dt: f64) -> bool {
+        let mut wish = Vec3::new(input.right, input.up, input.forward);
+        //diagonals shouldn't be faster than moving along one axis
+        if wish.get_len_squared() > 1_f64 {
+            wish = wish / wish.get_len();
+        }
+        let speed = self.max_speed
+            * match (input.sprint, input.slow) {
+                (true, false) => self.sprint_multiplier,
+                (false, true) => self.slow_multiplier,
+                _ => 1_f64,
+            };
+
+        //exponential smoothing is frame rate independent, unlike a fixed lerp per frame
+        let blend = 1_f64 - (-self.acceleration * dt).exp();
+        self.velocity += blend * (speed * wish - self.velocity);
+        let target_roll = input.roll.clamp(-1_f64, 1_f64) * self.max_roll_speed;
+        self.roll_velocity += blend * (target_roll - self.roll_velocity);
+
+        if self.velocity.get_len() < 1e-3 && self.roll_velocity.abs() < 1e-4 {
+            self.velocity = Vec3::new_empty();
+            self.roll_velocity = 0_f64;
+            return false;
+        }
+        cam.move_right(self.velocity.x() * dt);
+        cam.move_up(self.velocity.y() * dt);
+        cam.move_fwd(self.velocity.z() * dt);
+        cam.roll(self.roll_velocity * dt);
+        true
+    }
+}
+
+const MAX_SLIDES: usize = 4; //collisions resolved per step before giving up on the rest of it
+const SKIN: f64 = 1e-3; //gap kept to surfaces so the next sweep doesn't start touching them
+const WALKABLE: f64 = 0.7; //cosine of the steepest slope that still counts as ground
+const FALL_LIMIT: f64 = -100_f64; //below this the player is put back where walking started
+
+// Walking instead of flying: the player is a capsule under gravity that can jump and collides
+// with the world. The capsule is swept as a stack of spheres (Hittable::sweep) and whatever
+// part of a step runs into something slides along it instead, so walls stop you and slopes
+// up to WALKABLE can be walked up. Looking stays free, only movement is constrained.
+pub struct WalkController {
+    velocity: Vec3,
+    on_ground: bool,
+    spawn: Point3,         //eye position to respawn at after falling off the world
+    pub radius: f64,       //of the capsule
+    pub height: f64,       //of the capsule, feet to top of the head
+    pub eye_height: f64,   //camera height above the feet
+    pub walk_speed: f64,   //units per second
+    pub acceleration: f64, //how quickly velocity catches up with the input on the ground
+    pub air_control: f64,  //fraction of the acceleration left while airborne
+    pub gravity: f64,      //units per second squared
+    pub jump_speed: f64,   //upward speed a jump starts with
+    pub sprint_multiplier: f64,
+    pub slow_multiplier: f64,
+}
+
+impl WalkController {
+    //starts with the eye at the camera's current position
+    pub fn new(cam: &Camera) -> Self {
+        Self {
+            velocity: Vec3::new_empty(),
+            on_ground: false,
+            spawn: cam.lookfrom,
+            radius: 0.3,
+            height: 1.8,
+            eye_height: 1.6,
+            walk_speed: 4_f64,
+            acceleration: 12_f64,
+            air_control: 0.2,
+            gravity: 9.81,
+            jump_speed: 4.5,
+            sprint_multiplier: 2_f64,
+            slow_multiplier: 0.4,
+        }
+    }
+
+    //advances the player by dt seconds, up > 0 jumps. True if the camera moved
+    pub fn update(
+        &mut self,
+        cam: &mut Camera,
+        input: &MoveInput,
+        world: &dyn Hittable,
+        dt: f64,
+    ) -> bool {
+        let up = Vec3::new(0_f64, 1_f64, 0_f64);
+        //walk along the ground whichever way the camera is pitched. Rolled onto its side there's
+        //no sensible ground direction, so only falling and jumping still happen
+        let right = Vec3::new(cam.right().x(), 0_f64, cam.right().z());
+        let mut wish = match right.get_len_squared() < 1e-9 {
+            true => Vec3::new_empty(),
+            false => {
+                let right = unit_vector(&right);
+                let forward = cross(&up, &right);
+                input.forward * forward + input.right * right
+            }
+        };
+        if wish.get_len_squared() > 1_f64 {
+            wish = wish / wish.get_len();
+        }
+        let speed = self.walk_speed
+            * match (input.sprint, input.slow) {
+                (true, false) => self.sprint_multiplier,
+                (false, true) => self.slow_multiplier,
+                _ => 1_f64,
+            };
+        let acceleration = match self.on_ground {
+            true => self.acceleration,
+            false => self.acceleration * self.air_control,
+        };
+        let blend = 1_f64 - (-acceleration * dt).exp();
+        let horizontal = Vec3::new(self.velocity.x(), 0_f64, self.velocity.z());
+        let horizontal = horizontal + blend * (speed * wish - horizontal);
+        let mut vertical = self.velocity.y() - self.gravity * dt;
+        if self.on_ground && input.up > 0.5 {
+            vertical = self.jump_speed;
+        }
+        self.velocity = horizontal + vertical * up;
+
+        let start = cam.lookfrom - self.eye_height * up;
+        let mut feet = start;
+        let mut step = dt * self.velocity;
+        self.on_ground = false;
+        for _ in 0..MAX_SLIDES {
+            let length = step.get_len();
+            if length < 1e-9 {
+                break;
+            }
+            let Some((t, normal)) = self.sweep(world, feet, step) else {
+                feet += step;
+                break;
+            };
+            //stop just short of the contact, then slide the rest along the surface
+            feet += (t - SKIN / length).max(0_f64) * step;
+            step = (1_f64 - t) * step;
+            step = step - dot(&step, &normal) * normal;
+            let into = dot(&self.velocity, &normal);
+            if into < 0_f64 {
+                self.velocity = self.velocity - into * normal;
+            }
+            if normal.y() >= WALKABLE {
+                self.on_ground = true;
+            }
+        }
+
+        if feet.y() < FALL_LIMIT {
+            self.velocity = Vec3::new_empty();
+            cam.set_position(self.spawn);
+            return true;
+        }
+        if (feet - start).get_len_squared() < 1e-12 {
+            return false;
+        }
+        cam.set_position(feet + self.eye_height * up);
+        true
+    }
+
+    //earliest contact of the capsule moved by step from feet, as the fraction of the step
+    //and the surface normal there
+    fn sweep(&self, world: &dyn Hittable, feet: Point3, step: Vec3) -> Option<(f64, Vec3)> {
+        let up = Vec3::new(0_f64, 1_f64, 0_f64);
+        let span = (self.height - 2_f64 * self.radius).max(0_f64);
+        //spheres no further apart than the radius, so nothing much slips between them
+        let count = (span / self.radius).ceil() as usize + 1;
+        let mut earliest: Option<(f64, Vec3)> = None;
+        for i in 0..count {
+            let offset = self.radius + span * i as f64 / (count - 1).max(1) as f64;
+            let ray = Ray::new(feet + offset * up, step);
+            let limit = earliest.map_or(1_f64, |(t, _)| t);
+            let mut hit_rec: HitRecord = HitRecord::new_empty();
+            if world.sweep(&ray, self.radius, Interval::new(-1e-9, limit), &mut hit_rec) {
+                earliest = Some((hit_rec.t.max(0_f64), hit_rec.normal));
+            }
+        }
+        earliest
+    }
+}
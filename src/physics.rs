@@ -0,0 +1,157 @@
+use crate::{
+    scene::Scene,
+    vec3::{dot, Vec3},
+};
+
+const STEP_S: f64 = 1_f64 / 120_f64; //fixed physics step, so results don't depend on frame rate
+const MAX_STEPS: usize = 8; //per update, a long frame slows the simulation down instead
+const REST_SPEED: f64 = 0.2; //a supported ball slower than this falls asleep
+const SUPPORT: f64 = 0.7; //contact normal y above which a contact holds a ball up
+const FALL_LIMIT: f64 = -100_f64; //balls that fall past this without hitting anything are gone
+
+// Rigid body step for the scene's thrown balls. Balls fall under gravity and bounce off the
+// scene's planes, its spheres and each other, losing speed according to restitution and
+// rolling friction. Mass goes with volume, so a small ball bounces off a big one rather than
+// pushing it. Once a ball rests on something it falls asleep: it isn't moved any more, which
+// keeps a settled scene from re-rendering, but still has its mass and wakes up when a moving
+// ball runs into it. Balls that miss everything are dropped once they fall past FALL_LIMIT.
+pub struct Physics {
+    pub gravity: Vec3,
+    pub restitution: f64, //fraction of the normal speed kept by a bounce
+    pub friction: f64,    //per second damping of sliding/rolling speed while in contact
+    accumulator: f64,     //frame time not simulated yet
+}
+
+impl Physics {
+    pub fn new() -> Self {
+        Self {
+            gravity: Vec3::new(0_f64, -9.81, 0_f64),
+            restitution: 0.6,
+            friction: 1_f64,
+            accumulator: 0_f64,
+        }
+    }
+
+    //advances the moving balls by dt seconds, true if any of them moved
+    pub fn update(&mut self, scene: &mut Scene, dt: f64) -> bool {
+        if scene.balls.iter().all(|b| b.asleep) {
+            self.accumulator = 0_f64;
+            return false;
+        }
+        self.accumulator = (self.accumulator + dt).min(STEP_S * MAX_STEPS as f64);
+        let mut moved = false;
+        while self.accumulator >= STEP_S {
+            self.accumulator -= STEP_S;
+            self.step(scene, STEP_S);
+            moved = true;
+        }
+        moved
+    }
+
+    //sets every ball moving again, for when what they rest on may have changed
+    pub fn wake_all(&self, scene: &mut Scene) {
+        for ball in scene.balls.iter_mut() {
+            ball.asleep = false;
+        }
+    }
+
+    fn step(&self, scene: &mut Scene, dt: f64) {
+        let (balls, objects, planes) = (&mut scene.balls, &scene.objects, &scene.planes);
+        for ball in balls.iter_mut().filter(|b| !b.asleep) {
+            ball.velocity += dt * self.gravity;
+            ball.center += dt * ball.velocity;
+        }
+
+        //contacts push balls apart along the normal and remove the approaching speed
+        let mut supported = vec![false; balls.len()];
+        for i in 0..balls.len() {
+            if balls[i].asleep {
+                continue;
+            }
+            let mut velocity = balls[i].velocity;
+            //planes are solid on the side their normal faces
+            for plane in planes {
+                let distance =
+                    dot(&(balls[i].center - plane.point), &plane.normal) - balls[i].radius;
+                if distance < 0_f64 {
+                    balls[i].center += -distance * plane.normal;
+                    velocity = self.bounce(velocity, plane.normal, dt);
+                    supported[i] |= plane.normal.y() > SUPPORT;
+                }
+            }
+            //the scene's spheres don't move, i.e. have infinite mass
+            for object in objects {
+                let delta = balls[i].center - object.center;
+                let distance = delta.get_len();
+                let overlap = balls[i].radius + object.radius - distance;
+                if overlap <= 0_f64 || distance < 1e-9 {
+                    continue;
+                }
+                let normal = delta / distance;
+                balls[i].center += overlap * normal;
+                velocity = self.bounce(velocity, normal, dt);
+                supported[i] |= normal.y() > SUPPORT;
+            }
+            balls[i].velocity = velocity;
+
+            for j in 0..balls.len() {
+                if j == i || (!balls[j].asleep && j < i) {
+                    continue; //pairs of moving balls are handled once, from the lower index
+                }
+                let delta = balls[i].center - balls[j].center;
+                let distance = delta.get_len();
+                let overlap = balls[i].radius + balls[j].radius - distance;
+                if overlap <= 0_f64 || distance < 1e-9 {
+                    continue;
+                }
+                let normal = delta / distance; //from j to i
+                supported[i] |= normal.y() > SUPPORT;
+                supported[j] |= -normal.y() > SUPPORT;
+                balls[j].asleep = false;
+
+                let inv_mass = |k: usize| 1_f64 / balls[k].radius.powi(3).max(1e-9);
+                let (inv_i, inv_j) = (inv_mass(i), inv_mass(j));
+                let share_i = inv_i / (inv_i + inv_j);
+                balls[i].center += (overlap * share_i) * normal;
+                balls[j].center += -(overlap * (1_f64 - share_i)) * normal;
+
+                let approach = dot(&(balls[i].velocity - balls[j].velocity), &normal);
+                if approach < 0_f64 {
+                    let impulse = -(1_f64 + self.restitution) * approach / (inv_i + inv_j);
+                    let vi = balls[i].velocity + (impulse * inv_i) * normal;
+                    let vj = balls[j].velocity + -(impulse * inv_j) * normal;
+                    balls[i].velocity = self.damp(vi, normal, dt);
+                    balls[j].velocity = self.damp(vj, normal, dt);
+                }
+            }
+        }
+
+        for (ball, supported) in balls.iter_mut().zip(supported) {
+            if supported && !ball.asleep && ball.velocity.get_len() < REST_SPEED {
+                ball.asleep = true;
+                ball.velocity = Vec3::new_empty();
+            }
+        }
+        balls.retain(|ball| ball.center.y() >= FALL_LIMIT);
+    }
+
+    //velocity after hitting a fixed surface with this normal
+    fn bounce(&self, velocity: Vec3, normal: Vec3, dt: f64) -> Vec3 {
+        let approach = dot(&velocity, &normal);
+        if approach >= 0_f64 {
+            return velocity;
+        }
+        self.damp(
+            velocity - ((1_f64 + self.restitution) * approach) * normal,
+            normal,
+            dt,
+        )
+    }
+
+    //friction slows the part of the velocity along the contact surface
+    fn damp(&self, velocity: Vec3, normal: Vec3, dt: f64) -> Vec3 {
+        let along_normal = dot(&velocity, &normal) * normal;
+        let tangent = velocity - along_normal;
+        along_normal + (-self.friction * dt).exp() * tangent
+    }
+}
@@ -0,0 +1,84 @@
+use std::sync::Arc;
+
+use crate::{
+    interval::Interval,
+    ray::{HitRecord, Hittable, Ray, Scatter, SetFaceNormal},
+    vec3::{cross, dot, unit_vector, Point3, Vec3},
+};
+
+// Infinite plane through point, seen from both sides. Scenes use it for floors and walls.
+pub struct Plane {
+    point: Point3,
+    normal: Vec3,
+    mat: Option<Arc<dyn Scatter>>,
+}
+
+impl Plane {
+    pub fn new(point: Point3, normal: Vec3, material: Arc<dyn Scatter>) -> Plane {
+        Plane {
+            point,
+            normal: unit_vector(&normal),
+            mat: Some(material),
+        }
+    }
+
+    //ray against this plane moved `offset` along its normal, which is all a sphere sweep
+    //against it is
+    fn intersect(&self, offset: f64, ray: &Ray, ray_t: Interval, rec: &mut HitRecord) -> bool {
+        let denom = dot(&ray.dir(), &self.normal);
+        if denom.abs() < 1e-9 {
+            return false;
+        }
+        let to_plane = self.point + offset * self.normal - ray.origin();
+        let root = dot(&to_plane, &self.normal) / denom;
+        if !ray_t.surrounds(root) {
+            return false;
+        }
+        rec.t = root;
+        rec.p = ray.at(rec.t);
+        rec.set_face_normal(ray, &self.normal);
+        //one unit squares along two directions in the plane
+        let helper = match self.normal.x().abs() > 0.9 {
+            true => Vec3::new(0_f64, 1_f64, 0_f64),
+            false => Vec3::new(1_f64, 0_f64, 0_f64),
+        };
+        let tangent = unit_vector(&cross(&helper, &self.normal));
+        let bitangent = cross(&self.normal, &tangent);
+        let local = rec.p - self.point;
+        rec.u = dot(&local, &tangent).rem_euclid(1_f64);
+        rec.v = dot(&local, &bitangent).rem_euclid(1_f64);
+        rec.mat = self.mat.clone();
+        true
+    }
+}
+
+impl Hittable for Plane {
+    fn hit(&self, ray: &Ray, ray_t: Interval, rec: &mut HitRecord) -> bool {
+        self.intersect(0_f64, ray, ray_t, rec)
+    }
+
+    fn sweep(&self, ray: &Ray, radius: f64, ray_t: Interval, rec: &mut HitRecord) -> bool {
+        //the sphere stays on the side its center starts on
+        let height = dot(&(ray.origin() - self.point), &self.normal);
+        let side = if height < 0_f64 { -1_f64 } else { 1_f64 };
+        if height.abs() > radius {
+            if !self.intersect(side * radius, ray, ray_t, rec) {
+                return false;
+            }
+            rec.p = ray.at(rec.t);
+            rec.normal = side * self.normal;
+            rec.front_face = true;
+            return true;
+        }
+        //already overlapping: stop any motion further in right away, moving out is fine
+        if dot(&ray.dir(), &self.normal) * side >= 0_f64 {
+            return false;
+        }
+        rec.t = ray_t.min.max(0_f64);
+        rec.p = ray.origin();
+        rec.normal = side * self.normal;
+        rec.front_face = true;
+        rec.mat = self.mat.clone();
+        true
+    }
+}
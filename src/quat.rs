@@ -0,0 +1,117 @@
+use std::ops::Mul;
+
+use crate::vec3::{cross, dot, unit_vector, Vec3};
+
+// Unit quaternion used for camera orientation. Camera local axes follow the usual convention:
+// +x right, +y up and the camera looks down -z, so rotating those gives u, v and -w.
+#[derive(Copy, Clone, Debug)]
+pub struct Quat {
+    w: f64,
+    v: Vec3,
+}
+
+impl Quat {
+    pub fn identity() -> Self {
+        Self {
+            w: 1_f64,
+            v: Vec3::new_empty(),
+        }
+    }
+
+    pub fn from_axis_angle(axis: &Vec3, angle: f64) -> Self {
+        let half = angle / 2_f64;
+        Self {
+            w: half.cos(),
+            v: half.sin() * unit_vector(axis),
+        }
+    }
+
+    //rotation taking the local x/y/z axes onto the orthonormal basis right/up/back
+    pub fn from_basis(right: &Vec3, up: &Vec3, back: &Vec3) -> Self {
+        let trace = right.x() + up.y() + back.z();
+        let q = if trace > 0_f64 {
+            let s = 0.5 / (trace + 1_f64).sqrt();
+            Self {
+                w: 0.25 / s,
+                v: Vec3::new(
+                    (up.z() - back.y()) * s,
+                    (back.x() - right.z()) * s,
+                    (right.y() - up.x()) * s,
+                ),
+            }
+        } else if right.x() > up.y() && right.x() > back.z() {
+            let s = 2_f64 * (1_f64 + right.x() - up.y() - back.z()).sqrt();
+            Self {
+                w: (up.z() - back.y()) / s,
+                v: Vec3::new(
+                    0.25 * s,
+                    (up.x() + right.y()) / s,
+                    (back.x() + right.z()) / s,
+                ),
+            }
+        } else if up.y() > back.z() {
+            let s = 2_f64 * (1_f64 + up.y() - right.x() - back.z()).sqrt();
+            Self {
+                w: (back.x() - right.z()) / s,
+                v: Vec3::new((up.x() + right.y()) / s, 0.25 * s, (back.y() + up.z()) / s),
+            }
+        } else {
+            let s = 2_f64 * (1_f64 + back.z() - right.x() - up.y()).sqrt();
+            Self {
+                w: (right.y() - up.x()) / s,
+                v: Vec3::new(
+                    (back.x() + right.z()) / s,
+                    (back.y() + up.z()) / s,
+                    0.25 * s,
+                ),
+            }
+        };
+        q.normalized()
+    }
+
+    pub fn normalized(&self) -> Self {
+        let len = (self.w * self.w + self.v.get_len_squared()).sqrt();
+        Self {
+            w: self.w / len,
+            v: self.v / len,
+        }
+    }
+
+    pub fn rotate(&self, p: &Vec3) -> Vec3 {
+        //p' = p + 2w(v x p) + 2 v x (v x p)
+        let t = 2_f64 * cross(&self.v, p);
+        *p + self.w * t + cross(&self.v, &t)
+    }
+
+    pub fn right(&self) -> Vec3 {
+        self.rotate(&Vec3::new(1_f64, 0_f64, 0_f64))
+    }
+
+    pub fn up(&self) -> Vec3 {
+        self.rotate(&Vec3::new(0_f64, 1_f64, 0_f64))
+    }
+
+    pub fn forward(&self) -> Vec3 {
+        self.rotate(&Vec3::new(0_f64, 0_f64, -1_f64))
+    }
+
+    //yaw around world y (0 looking down -z, positive turning right), pitch above the horizon
+    //and roll (positive is clockwise), all in radians
+    pub fn yaw_pitch_roll(&self) -> (f64, f64, f64) {
+        let (forward, right, up) = (self.forward(), self.right(), self.up());
+        let yaw = forward.x().atan2(-forward.z());
+        let pitch = forward.y().clamp(-1_f64, 1_f64).asin();
+        let roll = (-right.y()).atan2(up.y());
+        (yaw, pitch, roll)
+    }
+}
+
+impl Mul for Quat {
+    type Output = Quat;
+    fn mul(self, rhs: Quat) -> Quat {
+        Quat {
+            w: self.w * rhs.w - dot(&self.v, &rhs.v),
+            v: self.w * rhs.v + rhs.w * self.v + cross(&self.v, &rhs.v),
+        }
+    }
+}
@@ -1,8 +1,9 @@
-use std::rc::Rc;
+use std::sync::Arc;
 
 use crate::{
     color::Color,
     interval::Interval,
+    sampler::Sampler,
     vec3::{dot, Point3},
     Vec3,
 };
@@ -11,9 +12,12 @@ use crate::{
 pub struct HitRecord {
     pub p: Point3,
     pub normal: Vec3,
-    pub mat: Option<Rc<dyn Scatter>>,
+    pub mat: Option<Arc<dyn Scatter>>,
     pub t: f64,
     pub front_face: bool,
+    pub object_id: Option<usize>, //index of the object hit in the top level HittableList
+    pub u: f64,                   //surface coordinates of the hit, both 0 to 1
+    pub v: f64,
 }
 
 pub struct Ray {
@@ -49,8 +53,28 @@ impl Ray {
     }
 }
 
-pub trait Hittable {
+pub trait Hittable: Send + Sync {
     fn hit(&self, ray: &Ray, ray_t: Interval, rec: &mut HitRecord) -> bool;
+
+    //hit that also counts the intersection tests it took, for the traversal cost view. A shape
+    //is one test, anything holding other hittables adds up what it tests of them
+    fn hit_counted(
+        &self,
+        ray: &Ray,
+        ray_t: Interval,
+        rec: &mut HitRecord,
+        tests: &mut u64,
+    ) -> bool {
+        *tests += 1;
+        self.hit(ray, ray_t, rec)
+    }
+
+    //sweeps a sphere of `radius` centered on the ray origin along the ray and reports the first
+    //contact, with rec.p the sphere's center there and rec.normal pointing away from the surface.
+    //Shapes that don't implement it are treated as if the sphere were a point
+    fn sweep(&self, ray: &Ray, _radius: f64, ray_t: Interval, rec: &mut HitRecord) -> bool {
+        self.hit(ray, ray_t, rec)
+    }
 }
 
 pub trait SetFaceNormal {
@@ -75,12 +99,15 @@ impl HitRecord {
             mat: None,
             t: 0_f64,
             front_face: false,
+            object_id: None,
+            u: 0_f64,
+            v: 0_f64,
         }
     }
 }
 
 pub struct HittableList {
-    objects: Vec<Rc<dyn Hittable>>,
+    objects: Vec<Arc<dyn Hittable>>,
 }
 
 impl HittableList {
@@ -89,24 +116,51 @@ impl HittableList {
             objects: Vec::new(),
         }
     }
-    pub fn add(&mut self, object: Rc<dyn Hittable>) {
+    pub fn add(&mut self, object: Arc<dyn Hittable>) {
         self.objects.push(object);
     }
-    pub fn drop_last(&mut self) {
-        self.objects.pop();
-    }
 }
 
 impl Hittable for HittableList {
     fn hit(&self, ray: &Ray, ray_t: Interval, rec: &mut HitRecord) -> bool {
+        self.hit_counted(ray, ray_t, rec, &mut 0)
+    }
+
+    //a flat list tests every object for every ray
+    fn hit_counted(
+        &self,
+        ray: &Ray,
+        ray_t: Interval,
+        rec: &mut HitRecord,
+        tests: &mut u64,
+    ) -> bool {
+        let mut temp_rec: HitRecord = HitRecord::new_empty();
+        let mut hit_anything: bool = false;
+        let mut closest_so_far = ray_t.max;
+
+        for (i, object) in self.objects.iter().enumerate() {
+            let ray_t = Interval::new(ray_t.min, closest_so_far);
+            if object.hit_counted(ray, ray_t, &mut temp_rec, tests) {
+                hit_anything = true;
+                closest_so_far = temp_rec.t;
+                temp_rec.object_id = Some(i);
+                *rec = temp_rec.clone()
+            }
+        }
+        hit_anything
+    }
+
+    fn sweep(&self, ray: &Ray, radius: f64, ray_t: Interval, rec: &mut HitRecord) -> bool {
         let mut temp_rec: HitRecord = HitRecord::new_empty();
         let mut hit_anything: bool = false;
         let mut closest_so_far = ray_t.max;
 
-        for object in &self.objects {
-            if object.hit(ray, Interval::new(ray_t.min, closest_so_far), &mut temp_rec) {
+        for (i, object) in self.objects.iter().enumerate() {
+            let ray_t = Interval::new(ray_t.min, closest_so_far);
+            if object.sweep(ray, radius, ray_t, &mut temp_rec) {
                 hit_anything = true;
                 closest_so_far = temp_rec.t;
+                temp_rec.object_id = Some(i);
                 *rec = temp_rec.clone()
             }
         }
@@ -114,12 +168,16 @@ impl Hittable for HittableList {
     }
 }
 
-pub trait Scatter {
+pub trait Scatter: Send + Sync {
     fn scatter(
         &self,
         ray_in: &Ray,
         hit_rec: &HitRecord,
         attenuation: &mut Color,
         scattered_ray: &mut Ray,
+        sampler: &mut dyn Sampler,
     ) -> bool;
+
+    //base color of the surface, for the albedo view
+    fn albedo(&self) -> Color;
 }
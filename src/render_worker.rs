@@ -0,0 +1,163 @@
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::Arc;
+use std::thread;
+use std::time::Instant;
+
+use crate::{
+    camera::{Camera, RenderControl},
+    color::write_color,
+    editor,
+    ray::HittableList,
+    temporal::TemporalHistory,
+};
+
+pub struct RenderJob {
+    pub passes: Vec<Camera>, //rendered in order, each finished pass is published as a frame
+    pub world: Option<Arc<HittableList>>, //only set when the world changed
+    pub keep_history: bool,  //the world update only moved balls, see worker_loop
+    pub show_heatmap: bool,
+    pub temporal: bool, //blend with the reprojected previous frame, see TemporalHistory
+    pub highlight: Option<usize>, //object to outline, see editor::highlight
+    pub reduced: bool,  //rendered below full quality by dynamic resolution
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct FrameStats {
+    pub size: (i32, i32),
+    pub render_s: f64,
+    pub rays: u64,
+    pub samples: u64,
+    pub reduced: bool,
+    pub complete: bool, //last pass of its job, nothing better is coming for this view
+}
+
+pub struct Frame {
+    pub image: Vec<u8>,
+    pub stats: FrameStats,
+}
+
+// Renders on a background thread so the event loop never waits on a frame. Submitting a job
+// cancels the one in flight, and frames from cancelled jobs are dropped, so only the latest
+// view ever reaches the screen. Jobs share the world the event loop built rather than copying
+// it, the worker keeps the newest one it got.
+pub struct RenderWorker {
+    jobs: Sender<(u64, Arc<RenderControl>, RenderJob)>,
+    frames: Receiver<(u64, Frame)>,
+    generation: u64,
+    control: Arc<RenderControl>,
+}
+
+impl RenderWorker {
+    pub fn spawn() -> Self {
+        let (jobs, job_rx) = channel::<(u64, Arc<RenderControl>, RenderJob)>();
+        let (frame_tx, frames) = channel::<(u64, Frame)>();
+        thread::spawn(move || worker_loop(job_rx, frame_tx));
+        Self {
+            jobs,
+            frames,
+            generation: 0,
+            control: Arc::new(RenderControl::default()),
+        }
+    }
+
+    pub fn submit(&mut self, job: RenderJob) {
+        self.control.cancel();
+        self.control = Arc::new(RenderControl::default());
+        self.generation += 1;
+        //the thread only goes away with the program
+        let _ = self.jobs.send((self.generation, self.control.clone(), job));
+    }
+
+    //progress of the pass currently rendering, 0 to 1
+    pub fn progress(&self) -> f64 {
+        self.control.progress()
+    }
+
+    //newest frame of the current job that arrived since the last call
+    pub fn latest_frame(&self) -> Option<Frame> {
+        self.frames
+            .try_iter()
+            .filter(|(generation, _)| *generation == self.generation)
+            .map(|(_, frame)| frame)
+            .last()
+    }
+}
+
+fn worker_loop(jobs: Receiver<(u64, Arc<RenderControl>, RenderJob)>, frames: Sender<(u64, Frame)>) {
+    let mut world = Arc::new(HittableList::new());
+    let mut history: Option<TemporalHistory> = None;
+    while let Ok(mut next) = jobs.recv() {
+        //skip straight to the newest job, but keep the newest world any skipped job carried
+        //and whether any of them was an edit
+        while let Ok(newer) = jobs.try_recv() {
+            let world_update = next.2.world.take();
+            let keep_history = next.2.keep_history;
+            next = newer;
+            next.2.world = next.2.world.take().or(world_update);
+            next.2.keep_history &= keep_history;
+        }
+        let (generation, control, job) = next;
+        if let Some(new_world) = &job.world {
+            world = new_world.clone();
+            //balls in flight fail the depth check where they were and where they are now, so
+            //the history can stay for them. Edits like a material swap change shading at the
+            //same depth, which would blend with stale history
+            if !job.keep_history {
+                history = None;
+            }
+        }
+        if !job.temporal {
+            history = None;
+        }
+
+        let pass_count = job.passes.len();
+        for (i, mut cam) in job.passes.into_iter().enumerate() {
+            if control.is_cancelled() {
+                break;
+            }
+            cam.control = Some(control.clone());
+            let start = Instant::now();
+            let mut pixels = cam.render_linear(world.as_ref());
+            if control.is_cancelled() {
+                break;
+            }
+            let complete = i + 1 == pass_count;
+            if job.temporal {
+                //a finished still frame is shown as rendered, it only seeds the history
+                let depth = cam.depths(world.as_ref());
+                history = match (history.take(), complete && !job.reduced) {
+                    (Some(mut h), false) => {
+                        pixels = h.accumulate(cam.clone(), pixels, depth);
+                        Some(h)
+                    }
+                    _ => Some(TemporalHistory::new(cam.clone(), pixels.clone(), depth)),
+                };
+            }
+            let render_s = start.elapsed().as_secs_f64();
+            let mut image: Vec<u8> = pixels
+                .iter()
+                .flat_map(|p| write_color(p).map(|c| c as u8))
+                .collect();
+            if job.show_heatmap {
+                image = cam.sample_heatmap();
+            }
+            let size = cam.dimensions();
+            if let Some(selected) = job.highlight {
+                let ids = cam.object_ids(world.as_ref());
+                editor::highlight(selected, &mut image, &ids, size.0 as usize);
+            }
+            let (rays, samples) = cam.render_stats();
+            let stats = FrameStats {
+                size,
+                render_s,
+                rays,
+                samples,
+                reduced: job.reduced,
+                complete,
+            };
+            if frames.send((generation, Frame { image, stats })).is_err() {
+                return;
+            }
+        }
+    }
+}
@@ -0,0 +1,143 @@
+use crate::camera::Camera;
+
+const MIN_SCALE: f64 = 0.25; //smallest fraction of the full width renders drop to
+const EDGE_SIGMA: f64 = 24_f64; //color distance (0-255 per channel) edges start at
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ScalingMode {
+    Off,
+    Bilinear,
+    EdgeAware, //bilinear, but taps that differ a lot from the nearest one count less
+}
+
+impl ScalingMode {
+    pub fn next(self) -> Self {
+        match self {
+            ScalingMode::Off => ScalingMode::Bilinear,
+            ScalingMode::Bilinear => ScalingMode::EdgeAware,
+            ScalingMode::EdgeAware => ScalingMode::Off,
+        }
+    }
+}
+
+// Keeps the viewer responsive while the camera moves by rendering fewer samples per pixel and
+// then fewer pixels, and upscaling the result back to the full size. The budget is the fraction
+// of the full quality cost (pixels * samples) a frame may spend, adjusted after every reduced
+// frame from how long it took compared to target_frame_s. Once the camera stops, the next frame
+// renders at full quality again.
+pub struct DynamicResolution {
+    pub mode: ScalingMode,
+    pub target_frame_s: f64,
+    budget: f64,
+    full_samples: i32,
+    reduced_last_frame: bool,
+}
+
+impl DynamicResolution {
+    pub fn new(target_frame_s: f64) -> Self {
+        Self {
+            mode: ScalingMode::EdgeAware,
+            target_frame_s,
+            budget: 1_f64,
+            full_samples: 1,
+            reduced_last_frame: false,
+        }
+    }
+
+    //true when the last frame was reduced but the camera has stopped, so it needs redoing
+    pub fn needs_full_render(&self, moving: bool) -> bool {
+        self.reduced_last_frame && !moving
+    }
+
+    //the camera to render this frame with, at lower quality if it's moving, and whether it is
+    pub fn frame_camera(&mut self, cam: &Camera, moving: bool) -> (Camera, bool) {
+        let mut frame_cam = cam.clone();
+        self.reduced_last_frame = moving && self.mode != ScalingMode::Off;
+        if !self.reduced_last_frame {
+            return (frame_cam, false);
+        }
+        let (width, spp) = (cam.dimensions().0, cam.samples_per_pixel());
+        self.full_samples = spp;
+
+        //samples go first since they cost the same as pixels and hurt less, then resolution
+        let samples = ((spp as f64 * self.budget).ceil() as i32).clamp(1, spp);
+        let pixel_fraction = (self.budget * spp as f64 / samples as f64).min(1_f64);
+        let scale = pixel_fraction.sqrt().max(MIN_SCALE);
+        frame_cam.set_width(((width as f64 * scale) as i32).max(1));
+        frame_cam.set_samples_per_pixel(samples);
+        frame_cam.max_samples_per_pixel = samples; //no adaptive top up while moving
+        (frame_cam, true)
+    }
+
+    //updates the budget from a reduced frame and scales it up to full_size
+    pub fn finish_frame(
+        &mut self,
+        image: Vec<u8>,
+        size: (i32, i32),
+        full_size: (i32, i32),
+        render_s: f64,
+    ) -> Vec<u8> {
+        //cost is roughly linear in the budget, the square root damps the correction so the
+        //resolution doesn't oscillate from frame to frame
+        let ratio = (self.target_frame_s / render_s.max(1e-4)).clamp(0.5, 2_f64);
+        let min_budget = MIN_SCALE * MIN_SCALE / self.full_samples as f64;
+        self.budget = (self.budget * ratio.sqrt()).clamp(min_budget, 1_f64);
+
+        if size == full_size {
+            return image;
+        }
+        upscale(&image, size, full_size, self.mode)
+    }
+}
+
+//resizes an rgb image, sampling at pixel centers
+fn upscale(src: &[u8], (sw, sh): (i32, i32), (dw, dh): (i32, i32), mode: ScalingMode) -> Vec<u8> {
+    let mut dst = vec![0u8; (dw * dh * 3) as usize];
+    let texel = |x: i32, y: i32| {
+        let i = ((y.clamp(0, sh - 1) * sw + x.clamp(0, sw - 1)) * 3) as usize;
+        [src[i] as f64, src[i + 1] as f64, src[i + 2] as f64]
+    };
+    for y in 0..dh {
+        let fy = ((y as f64 + 0.5) * sh as f64 / dh as f64 - 0.5).max(0_f64);
+        let (y0, ty) = (fy.floor() as i32, fy.fract());
+        for x in 0..dw {
+            let fx = ((x as f64 + 0.5) * sw as f64 / dw as f64 - 0.5).max(0_f64);
+            let (x0, tx) = (fx.floor() as i32, fx.fract());
+            let taps = [
+                (texel(x0, y0), (1_f64 - tx) * (1_f64 - ty)),
+                (texel(x0 + 1, y0), tx * (1_f64 - ty)),
+                (texel(x0, y0 + 1), (1_f64 - tx) * ty),
+                (texel(x0 + 1, y0 + 1), tx * ty),
+            ];
+            //the nearest tap decides which side of an edge this pixel is on
+            let nearest = taps
+                .iter()
+                .max_by(|a, b| a.1.total_cmp(&b.1))
+                .map(|t| t.0)
+                .unwrap_or([0_f64; 3]);
+
+            let mut sum = [0_f64; 3];
+            let mut weight_sum = 0_f64;
+            for (color, weight) in taps {
+                let weight = match mode {
+                    ScalingMode::EdgeAware => {
+                        let dist_sq: f64 = (0..3).map(|c| (color[c] - nearest[c]).powi(2)).sum();
+                        weight * (-dist_sq / (2_f64 * EDGE_SIGMA * EDGE_SIGMA)).exp()
+                    }
+                    _ => weight,
+                };
+                for c in 0..3 {
+                    sum[c] += weight * color[c];
+                }
+                weight_sum += weight;
+            }
+            let i = ((y * dw + x) * 3) as usize;
+            for c in 0..3 {
+                dst[i + c] = (sum[c] / weight_sum.max(1e-8))
+                    .round()
+                    .clamp(0_f64, 255_f64) as u8;
+            }
+        }
+    }
+    dst
+}
@@ -0,0 +1,348 @@
+use crate::helper::{hash, hash_to_f64, random_f64, stream_rng, Rng};
+
+// A sampler hands out the random numbers for one pixel sample at a time. Every pixel sample
+// restarts at dimension 0 and the camera/materials always ask for dimensions in the same order
+// (film offset, lens, then per bounce), so low discrepancy sequences line up dimension for
+// dimension across all the samples of a pixel.
+pub trait Sampler {
+    fn start_pixel_sample(&mut self, x: i32, y: i32, sample_idx: i32);
+    fn get_1d(&mut self) -> f64;
+    fn get_2d(&mut self) -> (f64, f64);
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SamplerKind {
+    Independent,
+    Stratified,
+    Halton,
+    Sobol,
+}
+
+impl SamplerKind {
+    pub fn next(self) -> Self {
+        match self {
+            SamplerKind::Independent => SamplerKind::Stratified,
+            SamplerKind::Stratified => SamplerKind::Halton,
+            SamplerKind::Halton => SamplerKind::Sobol,
+            SamplerKind::Sobol => SamplerKind::Independent,
+        }
+    }
+}
+
+pub fn make_sampler(kind: SamplerKind, seed: u64, samples_per_pixel: i32) -> Box<dyn Sampler> {
+    let samples_per_pixel = samples_per_pixel.max(1) as u32;
+    match kind {
+        SamplerKind::Independent => Box::new(IndependentSampler::new(seed)),
+        SamplerKind::Stratified => Box::new(StratifiedSampler::new(seed, samples_per_pixel)),
+        SamplerKind::Halton => Box::new(HaltonSampler::new(seed)),
+        SamplerKind::Sobol => Box::new(SobolSampler::new(seed, samples_per_pixel)),
+    }
+}
+
+//per pixel hash every sampler keys its randomisation on
+fn pixel_hash(seed: u64, x: i32, y: i32) -> u64 {
+    hash(&[seed, x as u32 as u64, y as u32 as u64])
+}
+
+//Kensler's hashed permutation: element i of a random permutation of 0..l picked by p
+fn permutation_element(mut i: u32, l: u32, p: u32) -> u32 {
+    let mut w = l - 1;
+    w |= w >> 1;
+    w |= w >> 2;
+    w |= w >> 4;
+    w |= w >> 8;
+    w |= w >> 16;
+    loop {
+        i ^= p;
+        i = i.wrapping_mul(0xe170893d);
+        i ^= p >> 16;
+        i ^= (i & w) >> 4;
+        i ^= p >> 8;
+        i = i.wrapping_mul(0x0929eb3f);
+        i ^= p >> 23;
+        i ^= (i & w) >> 1;
+        i = i.wrapping_mul(1 | p >> 27);
+        i = i.wrapping_mul(0x6935fa69);
+        i ^= (i & w) >> 11;
+        i = i.wrapping_mul(0x74dcb303);
+        i ^= (i & w) >> 2;
+        i = i.wrapping_mul(0x9e501cc3);
+        i ^= (i & w) >> 2;
+        i = i.wrapping_mul(0xc860a3df);
+        i &= w;
+        i ^= i >> 5;
+        if i < l {
+            break;
+        }
+    }
+    (i.wrapping_add(p)) % l
+}
+
+//shuffle the sample index within each block of `count` samples, so every dimension visits the
+//strata/points in a different order and the dimensions don't correlate with each other
+fn shuffled_index(sample_idx: u32, count: u32, h: u64) -> u32 {
+    let block = sample_idx / count;
+    block * count + permutation_element(sample_idx % count, count, h as u32)
+}
+
+pub struct IndependentSampler {
+    seed: u64,
+    rng: Rng,
+    pixel: (i32, i32),
+    next_sample: i32,
+}
+
+impl IndependentSampler {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            rng: stream_rng(seed, 0),
+            pixel: (i32::MIN, i32::MIN),
+            next_sample: -1,
+        }
+    }
+}
+
+impl Sampler for IndependentSampler {
+    fn start_pixel_sample(&mut self, x: i32, y: i32, sample_idx: i32) {
+        //consecutive samples of a pixel keep drawing from one stream, we only reseed on a jump
+        //so the numbers depend on (seed, pixel, first sample) and never on render order
+        if self.pixel != (x, y) || self.next_sample != sample_idx {
+            let stream = hash(&[x as u32 as u64, y as u32 as u64, sample_idx as u32 as u64]);
+            self.rng = stream_rng(self.seed, stream);
+        }
+        self.pixel = (x, y);
+        self.next_sample = sample_idx + 1;
+    }
+
+    fn get_1d(&mut self) -> f64 {
+        random_f64(&mut self.rng)
+    }
+
+    fn get_2d(&mut self) -> (f64, f64) {
+        (random_f64(&mut self.rng), random_f64(&mut self.rng))
+    }
+}
+
+pub struct StratifiedSampler {
+    seed: u64,
+    samples_per_pixel: u32,
+    x_strata: u32,
+    y_strata: u32,
+    pixel: u64,
+    sample_idx: u32,
+    dim: u64,
+}
+
+impl StratifiedSampler {
+    pub fn new(seed: u64, samples_per_pixel: u32) -> Self {
+        let x_strata = ((samples_per_pixel as f64).sqrt() as u32).max(1);
+        Self {
+            seed,
+            samples_per_pixel,
+            x_strata,
+            y_strata: (samples_per_pixel / x_strata).max(1),
+            pixel: 0,
+            sample_idx: 0,
+            dim: 0,
+        }
+    }
+
+    fn jitter(&self, axis: u64) -> f64 {
+        hash_to_f64(hash(&[self.pixel, self.dim, self.sample_idx as u64, axis]))
+    }
+}
+
+impl Sampler for StratifiedSampler {
+    fn start_pixel_sample(&mut self, x: i32, y: i32, sample_idx: i32) {
+        self.pixel = pixel_hash(self.seed, x, y);
+        self.sample_idx = sample_idx as u32;
+        self.dim = 0;
+    }
+
+    fn get_1d(&mut self) -> f64 {
+        let count = self.samples_per_pixel;
+        let stratum = shuffled_index(self.sample_idx, count, hash(&[self.pixel, self.dim])) % count;
+        let u = (stratum as f64 + self.jitter(0)) / count as f64;
+        self.dim += 1;
+        u
+    }
+
+    fn get_2d(&mut self) -> (f64, f64) {
+        let count = self.x_strata * self.y_strata;
+        let stratum = shuffled_index(self.sample_idx, count, hash(&[self.pixel, self.dim])) % count;
+        let (sx, sy) = (stratum % self.x_strata, stratum / self.x_strata);
+        let u = (
+            (sx as f64 + self.jitter(0)) / self.x_strata as f64,
+            (sy as f64 + self.jitter(1)) / self.y_strata as f64,
+        );
+        self.dim += 2;
+        u
+    }
+}
+
+//dimensions past the prime table fall back to hashed uniform numbers
+const HALTON_DIMENSIONS: usize = 256;
+
+pub struct HaltonSampler {
+    seed: u64,
+    primes: Vec<u64>,
+    pixel: u64,
+    sample_idx: u64,
+    dim: usize,
+}
+
+impl HaltonSampler {
+    pub fn new(seed: u64) -> Self {
+        let mut primes: Vec<u64> = Vec::with_capacity(HALTON_DIMENSIONS);
+        let mut candidate = 2_u64;
+        while primes.len() < HALTON_DIMENSIONS {
+            if primes.iter().all(|p| !candidate.is_multiple_of(*p)) {
+                primes.push(candidate);
+            }
+            candidate += 1;
+        }
+        Self {
+            seed,
+            primes,
+            pixel: 0,
+            sample_idx: 0,
+            dim: 0,
+        }
+    }
+
+    fn radical_inverse(base: u64, mut idx: u64) -> f64 {
+        let inv_base = 1_f64 / base as f64;
+        let mut inv_base_m = 1_f64;
+        let mut reversed = 0_u64;
+        while idx > 0 {
+            reversed = reversed * base + idx % base;
+            idx /= base;
+            inv_base_m *= inv_base;
+        }
+        (reversed as f64 * inv_base_m).min(1_f64 - f64::EPSILON)
+    }
+
+    fn sample_dimension(&self, dim: usize) -> f64 {
+        //every pixel walks the same sequence, a per pixel Cranley-Patterson rotation keeps
+        //neighbouring pixels from sharing the exact same points
+        let offset = hash_to_f64(hash(&[self.pixel, dim as u64]));
+        match self.primes.get(dim) {
+            Some(&base) => (Self::radical_inverse(base, self.sample_idx) + offset).fract(),
+            None => hash_to_f64(hash(&[self.pixel, dim as u64, self.sample_idx])),
+        }
+    }
+}
+
+impl Sampler for HaltonSampler {
+    fn start_pixel_sample(&mut self, x: i32, y: i32, sample_idx: i32) {
+        self.pixel = pixel_hash(self.seed, x, y);
+        self.sample_idx = sample_idx as u64;
+        self.dim = 0;
+    }
+
+    fn get_1d(&mut self) -> f64 {
+        let u = self.sample_dimension(self.dim);
+        self.dim += 1;
+        u
+    }
+
+    fn get_2d(&mut self) -> (f64, f64) {
+        let u = (
+            self.sample_dimension(self.dim),
+            self.sample_dimension(self.dim + 1),
+        );
+        self.dim += 2;
+        u
+    }
+}
+
+// Padded, Owen scrambled Sobol: each 1d/2d request uses the first two Sobol dimensions (which
+// are (0,2)-sequences in 2d) with its own index shuffle and scramble, so we get Sobol quality
+// per dimension pair without needing a table of direction numbers.
+pub struct SobolSampler {
+    seed: u64,
+    samples_per_pixel: u32,
+    pixel: u64,
+    sample_idx: u32,
+    dim: u64,
+}
+
+impl SobolSampler {
+    pub fn new(seed: u64, samples_per_pixel: u32) -> Self {
+        Self {
+            seed,
+            samples_per_pixel,
+            pixel: 0,
+            sample_idx: 0,
+            dim: 0,
+        }
+    }
+
+    fn sobol_dim0(idx: u32) -> u32 {
+        idx.reverse_bits()
+    }
+
+    fn sobol_dim1(mut idx: u32) -> u32 {
+        let mut v = 1_u32 << 31;
+        let mut result = 0_u32;
+        while idx != 0 {
+            if idx & 1 != 0 {
+                result ^= v;
+            }
+            idx >>= 1;
+            v ^= v >> 1;
+        }
+        result
+    }
+
+    //hash based nested uniform (Owen) scramble, Laine-Karras style
+    fn owen_scramble(v: u32, seed: u32) -> u32 {
+        let mut v = v.reverse_bits();
+        v ^= v.wrapping_mul(0x3d20adea);
+        v = v.wrapping_add(seed);
+        v = v.wrapping_mul((seed >> 16) | 1);
+        v ^= v.wrapping_mul(0x05526c56);
+        v ^= v.wrapping_mul(0x53a22864);
+        v.reverse_bits()
+    }
+
+    fn to_unit(v: u32) -> f64 {
+        (v as f64 / (1_u64 << 32) as f64).min(1_f64 - f64::EPSILON)
+    }
+
+    fn index_for_dim(&self) -> u32 {
+        shuffled_index(
+            self.sample_idx,
+            self.samples_per_pixel,
+            hash(&[self.pixel, self.dim]),
+        )
+    }
+}
+
+impl Sampler for SobolSampler {
+    fn start_pixel_sample(&mut self, x: i32, y: i32, sample_idx: i32) {
+        self.pixel = pixel_hash(self.seed, x, y);
+        self.sample_idx = sample_idx as u32;
+        self.dim = 0;
+    }
+
+    fn get_1d(&mut self) -> f64 {
+        let scramble = hash(&[self.pixel, self.dim, 0]) as u32;
+        let v = Self::owen_scramble(Self::sobol_dim0(self.index_for_dim()), scramble);
+        self.dim += 1;
+        Self::to_unit(v)
+    }
+
+    fn get_2d(&mut self) -> (f64, f64) {
+        let idx = self.index_for_dim();
+        let scramble_x = hash(&[self.pixel, self.dim, 0]) as u32;
+        let scramble_y = hash(&[self.pixel, self.dim, 1]) as u32;
+        let u = (
+            Self::to_unit(Self::owen_scramble(Self::sobol_dim0(idx), scramble_x)),
+            Self::to_unit(Self::owen_scramble(Self::sobol_dim1(idx), scramble_y)),
+        );
+        self.dim += 2;
+        u
+    }
+}
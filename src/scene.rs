@@ -0,0 +1,245 @@
+use std::fs;
+use std::sync::Arc;
+
+use crate::{
+    color::Color,
+    material::{Dielectric, Lambertian, Metal},
+    plane::Plane,
+    ray::{HittableList, Scatter},
+    sphere::Sphere,
+    vec3::{unit_vector, Point3, Vec3},
+};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MaterialDesc {
+    Lambertian(Color),
+    Metal(Color, f64), //albedo, fuzz
+    Dielectric(f64),   //refraction index
+}
+
+//what the material swap key cycles through
+const MATERIAL_PALETTE: [MaterialDesc; 5] = [
+    MaterialDesc::Lambertian(Color::new(0.5, 0.5, 0.5)),
+    MaterialDesc::Lambertian(Color::new(1.0, 0.1, 0.1)),
+    MaterialDesc::Metal(Color::new(0.01, 0.01, 0.3), 0.0),
+    MaterialDesc::Metal(Color::new(0.8, 0.6, 0.2), 0.3),
+    MaterialDesc::Dielectric(1.5),
+];
+
+impl MaterialDesc {
+    pub fn build(&self) -> Arc<dyn Scatter> {
+        match *self {
+            MaterialDesc::Lambertian(albedo) => Arc::new(Lambertian::new(albedo)),
+            MaterialDesc::Metal(albedo, fuzz) => Arc::new(Metal::new(albedo, fuzz)),
+            MaterialDesc::Dielectric(refraction_idx) => Arc::new(Dielectric::new(refraction_idx)),
+        }
+    }
+
+    //the palette entry after this one (or the first, for materials not in the palette)
+    pub fn next_in_palette(&self) -> MaterialDesc {
+        match MATERIAL_PALETTE.iter().position(|m| m == self) {
+            Some(i) => MATERIAL_PALETTE[(i + 1) % MATERIAL_PALETTE.len()],
+            None => MATERIAL_PALETTE[0],
+        }
+    }
+
+    fn to_scene_string(self) -> String {
+        match self {
+            MaterialDesc::Lambertian(a) => format!("lambertian {} {} {}", a.x(), a.y(), a.z()),
+            MaterialDesc::Metal(a, fuzz) => {
+                format!("metal {} {} {} {}", a.x(), a.y(), a.z(), fuzz)
+            }
+            MaterialDesc::Dielectric(refraction_idx) => format!("dielectric {}", refraction_idx),
+        }
+    }
+
+    fn parse(parts: &[&str]) -> Result<Self, String> {
+        let values: Vec<f64> = parts[1..]
+            .iter()
+            .map(|v| v.parse::<f64>().map_err(|e| e.to_string()))
+            .collect::<Result<_, _>>()?;
+        match (parts[0], values.len()) {
+            ("lambertian", 3) => Ok(MaterialDesc::Lambertian(Color::new(
+                values[0], values[1], values[2],
+            ))),
+            ("metal", 4) => Ok(MaterialDesc::Metal(
+                Color::new(values[0], values[1], values[2]),
+                values[3],
+            )),
+            ("dielectric", 1) => Ok(MaterialDesc::Dielectric(values[0])),
+            _ => Err(format!("bad material '{}'", parts.join(" "))),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct SceneObject {
+    pub center: Point3,
+    pub radius: f64,
+    pub material: MaterialDesc,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct ScenePlane {
+    pub point: Point3,
+    pub normal: Vec3, //unit length, the side balls bounce off
+    pub material: MaterialDesc,
+}
+
+//a thrown ball. Balls live apart from the objects, so they're never saved, picked or edited
+#[derive(Clone, Copy, Debug)]
+pub struct Ball {
+    pub center: Point3,
+    pub radius: f64,
+    pub material: MaterialDesc,
+    pub velocity: Vec3,
+    pub asleep: bool, //came to rest and isn't simulated until something hits it, see Physics
+}
+
+// Editable description of the world, the HittableList the camera renders is built from this.
+// Scene files are plain text, one object per line:
+//   sphere <x> <y> <z> <radius> lambertian <r> <g> <b>
+//   sphere <x> <y> <z> <radius> metal <r> <g> <b> <fuzz>
+//   sphere <x> <y> <z> <radius> dielectric <refraction index>
+//   plane <x> <y> <z> <nx> <ny> <nz> <material>
+// where a plane goes through (x, y, z) facing (nx, ny, nz) and takes the same materials as a
+// sphere. Blank lines and lines starting with # are skipped. Thrown balls aren't saved.
+#[derive(Clone)]
+pub struct Scene {
+    pub objects: Vec<SceneObject>,
+    pub planes: Vec<ScenePlane>, //not editable, they come after objects in the world
+    pub balls: Vec<Ball>,        //thrown while running, after the planes in the world
+}
+
+impl Scene {
+    //the original demo scene: ground, glass, red and blue metal balls
+    pub fn default_scene() -> Self {
+        let sphere = |x: f64, y: f64, z: f64, radius: f64, material: MaterialDesc| SceneObject {
+            center: Point3::new(x, y, z),
+            radius,
+            material,
+        };
+        Self {
+            objects: vec![
+                sphere(0.0, -1000.0, 0.0, 1000.0, MATERIAL_PALETTE[0]),
+                sphere(0.0, 1.0, 0.0, 1.0, MaterialDesc::Dielectric(1.5)),
+                sphere(-3.0, 1.0, 0.0, 1.0, MATERIAL_PALETTE[1]),
+                sphere(3.0, 1.0, 0.0, 1.0, MATERIAL_PALETTE[2]),
+            ],
+            planes: Vec::new(),
+            balls: Vec::new(),
+        }
+    }
+
+    pub fn load(path: &str) -> Result<Self, String> {
+        let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+        let mut objects: Vec<SceneObject> = Vec::new();
+        let mut planes: Vec<ScenePlane> = Vec::new();
+        for (line_no, line) in text.lines().enumerate() {
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            match parts.first() {
+                None => {}
+                Some(p) if p.starts_with('#') => {}
+                Some(&"sphere") if parts.len() > 5 => {
+                    let err = |e: String| format!("{} line {}: {}", path, line_no + 1, e);
+                    let values: Vec<f64> = parts[1..5]
+                        .iter()
+                        .map(|v| v.parse::<f64>().map_err(|e| err(e.to_string())))
+                        .collect::<Result<_, _>>()?;
+                    objects.push(SceneObject {
+                        center: Point3::new(values[0], values[1], values[2]),
+                        radius: values[3],
+                        material: MaterialDesc::parse(&parts[5..]).map_err(err)?,
+                    });
+                }
+                Some(&"plane") if parts.len() > 7 => {
+                    let err = |e: String| format!("{} line {}: {}", path, line_no + 1, e);
+                    let values: Vec<f64> = parts[1..7]
+                        .iter()
+                        .map(|v| v.parse::<f64>().map_err(|e| err(e.to_string())))
+                        .collect::<Result<_, _>>()?;
+                    let normal = Vec3::new(values[3], values[4], values[5]);
+                    if normal.near_zero() {
+                        return Err(err("plane normal can't be zero".to_string()));
+                    }
+                    planes.push(ScenePlane {
+                        point: Point3::new(values[0], values[1], values[2]),
+                        normal: unit_vector(&normal),
+                        material: MaterialDesc::parse(&parts[7..]).map_err(err)?,
+                    });
+                }
+                _ => {
+                    return Err(format!(
+                        "{} line {}: can't parse '{}'",
+                        path,
+                        line_no + 1,
+                        line
+                    ))
+                }
+            }
+        }
+        Ok(Self {
+            objects,
+            planes,
+            balls: Vec::new(),
+        })
+    }
+
+    pub fn save(&self, path: &str) -> std::io::Result<()> {
+        let mut text = String::from("# sphere <x> <y> <z> <radius> <material>\n");
+        text += "# plane <x> <y> <z> <nx> <ny> <nz> <material>\n";
+        for object in &self.objects {
+            let c = object.center;
+            text += &format!(
+                "sphere {} {} {} {} {}\n",
+                c.x(),
+                c.y(),
+                c.z(),
+                object.radius,
+                object.material.to_scene_string()
+            );
+        }
+        for plane in &self.planes {
+            let (p, n) = (plane.point, plane.normal);
+            text += &format!(
+                "plane {} {} {} {} {} {} {}\n",
+                p.x(),
+                p.y(),
+                p.z(),
+                n.x(),
+                n.y(),
+                n.z(),
+                plane.material.to_scene_string()
+            );
+        }
+        fs::write(path, text)
+    }
+
+    //object ids in the returned list match indices into objects, the planes and then the
+    //balls follow them
+    pub fn to_world(&self) -> HittableList {
+        let mut world = HittableList::new();
+        for object in &self.objects {
+            world.add(Arc::new(Sphere::new(
+                object.center,
+                object.radius,
+                object.material.build(),
+            )));
+        }
+        for plane in &self.planes {
+            world.add(Arc::new(Plane::new(
+                plane.point,
+                plane.normal,
+                plane.material.build(),
+            )));
+        }
+        for ball in &self.balls {
+            world.add(Arc::new(Sphere::new(
+                ball.center,
+                ball.radius,
+                ball.material.build(),
+            )));
+        }
+        world
+    }
+}
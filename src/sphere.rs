@@ -1,9 +1,10 @@
-use std::rc::Rc;
+use std::sync::Arc;
 
 use crate::ray::HitRecord;
 use crate::ray::Hittable;
 use crate::ray::Ray;
 use crate::{
+    helper::PI,
     interval::Interval,
     ray::{Scatter, SetFaceNormal},
     vec3::{dot, Point3},
@@ -12,11 +13,11 @@ use crate::{
 pub struct Sphere {
     center: Point3,
     radius: f64,
-    mat: Option<Rc<dyn Scatter>>,
+    mat: Option<Arc<dyn Scatter>>,
 }
 
 impl Sphere {
-    pub fn new(_center: Point3, _radius: f64, material: Rc<dyn Scatter>) -> Sphere {
+    pub fn new(_center: Point3, _radius: f64, material: Arc<dyn Scatter>) -> Sphere {
         Sphere {
             center: _center,
             radius: _radius.max(0_f64),
@@ -25,12 +26,14 @@ impl Sphere {
     }
 }
 
-impl Hittable for Sphere {
-    fn hit(&self, ray: &Ray, ray_t: Interval, rec: &mut HitRecord) -> bool {
+impl Sphere {
+    //ray against this sphere grown by `grow`, which is all a sphere sweep against it is
+    fn intersect(&self, grow: f64, ray: &Ray, ray_t: Interval, rec: &mut HitRecord) -> bool {
+        let radius = self.radius + grow;
         let oc = self.center - ray.origin();
         let a = ray.dir().get_len_squared();
         let h = dot(&ray.dir(), &oc);
-        let c = oc.get_len_squared() - self.radius.powi(2);
+        let c = oc.get_len_squared() - radius.powi(2);
         let discriminant = h.powi(2) - a * c;
         match discriminant {
             d if d < 0_f64 => false,
@@ -45,11 +48,40 @@ impl Hittable for Sphere {
                 }
                 rec.t = root;
                 rec.p = ray.at(rec.t);
-                let outward_normal = (rec.p - self.center) / self.radius;
+                let outward_normal = (rec.p - self.center) / radius;
                 rec.set_face_normal(ray, &outward_normal);
+                //longitude around y starting at -x, latitude from the bottom pole up
+                let theta = (-outward_normal.y()).clamp(-1_f64, 1_f64).acos();
+                let phi = (-outward_normal.z()).atan2(outward_normal.x()) + PI;
+                rec.u = phi / (2_f64 * PI);
+                rec.v = theta / PI;
                 rec.mat = self.mat.clone();
                 true
             }
         }
     }
 }
+
+impl Hittable for Sphere {
+    fn hit(&self, ray: &Ray, ray_t: Interval, rec: &mut HitRecord) -> bool {
+        self.intersect(0_f64, ray, ray_t, rec)
+    }
+
+    fn sweep(&self, ray: &Ray, radius: f64, ray_t: Interval, rec: &mut HitRecord) -> bool {
+        let oc = self.center - ray.origin();
+        let reach = self.radius + radius;
+        if oc.get_len_squared() >= reach * reach {
+            return self.intersect(radius, ray, ray_t, rec);
+        }
+        //already overlapping: stop any motion further in right away, moving out is fine
+        if dot(&ray.dir(), &oc) <= 0_f64 {
+            return false;
+        }
+        rec.t = ray_t.min.max(0_f64);
+        rec.p = ray.origin();
+        rec.normal = -oc / oc.get_len().max(1e-9);
+        rec.front_face = true;
+        rec.mat = self.mat.clone();
+        true
+    }
+}
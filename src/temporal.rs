@@ -0,0 +1,123 @@
+use crate::{
+    camera::{Camera, Projection, StereoMode},
+    color::Color,
+    vec3::Point3,
+};
+
+const MAX_HISTORY_SAMPLES: f64 = 16_f64; //how much old shading may outweigh a new frame
+const DEPTH_TOLERANCE: f64 = 0.05; //relative depth difference that counts as a different surface
+const SKY_DEPTH: f64 = 1e6; //far enough that only rotation moves the sky
+
+// Reuses the previous frame while the camera moves. Every pixel of a new frame finds the point
+// it sees in the previous frame through the depth under it and the two cameras, and blends with
+// what the previous frame had there weighted by how many samples went into each. History taps
+// whose depth doesn't match the point are a surface that was hidden before (a disocclusion)
+// and get dropped, so new geometry starts fresh instead of smearing the old one over it.
+pub struct TemporalHistory {
+    cam: Camera,
+    radiance: Vec<Color>,
+    depth: Vec<f64>,
+    samples: Vec<f64>, //effective samples behind every pixel
+}
+
+impl TemporalHistory {
+    //a history holding only this frame
+    pub fn new(cam: Camera, radiance: Vec<Color>, depth: Vec<f64>) -> Self {
+        let samples = vec![cam.samples_per_pixel() as f64; radiance.len()];
+        Self {
+            cam,
+            radiance,
+            depth,
+            samples,
+        }
+    }
+
+    //blends a frame rendered by cam with the reprojected history and keeps the result as the
+    //new history. cam has to be the camera that rendered radiance, so it's initialized. Only
+    //mono perspective frames with the same projection as the history blend, anything else
+    //comes back as rendered and starts a new history
+    pub fn accumulate(&mut self, cam: Camera, radiance: Vec<Color>, depth: Vec<f64>) -> Vec<Color> {
+        if !self.can_reproject(&cam) {
+            *self = Self::new(cam, radiance.clone(), depth);
+            return radiance;
+        }
+        let (width, height) = cam.dimensions();
+        let new_samples = cam.samples_per_pixel() as f64;
+        let mut blended = Vec::with_capacity(radiance.len());
+        let mut samples = Vec::with_capacity(radiance.len());
+        for y in 0..height {
+            for x in 0..width {
+                let i = (y * width + x) as usize;
+                let point = cam.pixel_point(x, y, depth[i].min(SKY_DEPTH));
+                match self.sample(point, depth[i].is_infinite()) {
+                    Some((old, old_samples)) => {
+                        let old_samples = old_samples.min(MAX_HISTORY_SAMPLES);
+                        let t = new_samples / (new_samples + old_samples);
+                        blended.push(t * radiance[i] + (1_f64 - t) * old);
+                        samples.push(new_samples + old_samples);
+                    }
+                    None => {
+                        blended.push(radiance[i]);
+                        samples.push(new_samples);
+                    }
+                }
+            }
+        }
+        *self = Self {
+            cam,
+            radiance: blended.clone(),
+            depth,
+            samples,
+        };
+        blended
+    }
+
+    //pixel_point and project only handle mono perspective, and a different fov, projection or
+    //debug view makes the history a different image rather than a moved one
+    fn can_reproject(&self, cam: &Camera) -> bool {
+        cam.projection == Projection::Perspective
+            && cam.stereo == StereoMode::Mono
+            && cam.projection == self.cam.projection
+            && cam.stereo == self.cam.stereo
+            && cam.vfov() == self.cam.vfov()
+            && cam.debug_view == self.cam.debug_view
+    }
+
+    //bilinear lookup of the history at a world point, skipping taps of other surfaces
+    fn sample(&self, point: Point3, sky: bool) -> Option<(Color, f64)> {
+        let (px, py, expected) = self.cam.project(point)?;
+        let (width, height) = self.cam.dimensions();
+        let (x0, y0) = (px.floor() as i32, py.floor() as i32);
+        let (tx, ty) = (px - x0 as f64, py - y0 as f64);
+
+        let mut color = Color::new_empty();
+        let mut samples = 0_f64;
+        let mut weight_sum = 0_f64;
+        for (dx, dy, weight) in [
+            (0, 0, (1_f64 - tx) * (1_f64 - ty)),
+            (1, 0, tx * (1_f64 - ty)),
+            (0, 1, (1_f64 - tx) * ty),
+            (1, 1, tx * ty),
+        ] {
+            let (x, y) = (x0 + dx, y0 + dy);
+            if x < 0 || y < 0 || x >= width || y >= height || weight <= 0_f64 {
+                continue;
+            }
+            let i = (y * width + x) as usize;
+            let same_surface = match sky {
+                true => self.depth[i].is_infinite(),
+                false => (self.depth[i] - expected).abs() <= DEPTH_TOLERANCE * expected,
+            };
+            if same_surface {
+                color += weight * self.radiance[i];
+                samples += weight * self.samples[i];
+                weight_sum += weight;
+            }
+        }
+        //mostly disoccluded, a sliver of history would only add noise of its own
+        if weight_sum < 0.25 {
+            return None;
+        }
+        Some((color / weight_sum, samples / weight_sum))
+    }
+}
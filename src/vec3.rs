@@ -7,10 +7,9 @@ use std::ops::MulAssign;
 use std::ops::Neg;
 use std::ops::Sub;
 
-use crate::helper::random_f64;
-use crate::helper::random_f64_range;
+use crate::helper::PI;
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub struct Vec3 {
     e: [f64; 3],
 }
@@ -18,7 +17,7 @@ pub struct Vec3 {
 pub type Point3 = Vec3;
 
 impl Vec3 {
-    pub fn new(x: f64, y: f64, z: f64) -> Self {
+    pub const fn new(x: f64, y: f64, z: f64) -> Self {
         Self { e: [x, y, z] }
     }
 
@@ -60,18 +59,6 @@ impl Vec3 {
         let s = 1e-8;
         self.e[0].abs() < s && self.e[1].abs() < s && self.e[2].abs() < s
     }
-
-    pub fn random() -> Self {
-        Self::new(random_f64(), random_f64(), random_f64())
-    }
-
-    pub fn random_range(min: f64, max: f64) -> Self {
-        Self::new(
-            random_f64_range(min, max),
-            random_f64_range(min, max),
-            random_f64_range(min, max),
-        )
-    }
 }
 
 impl Neg for Vec3 {
@@ -184,22 +171,12 @@ pub fn unit_vector(u: &Vec3) -> Vec3 {
     *u / u.get_len()
 }
 
-pub fn random_unit_vector() -> Vec3 {
-    loop {
-        let p = Vec3::random_range(-1_f64, 1_f64);
-        let lensq = p.get_len_squared();
-        if lensq <= 1_f64 && 1e-160_f64 < lensq {
-            return p / lensq.sqrt();
-        }
-    }
-}
-
-pub fn random_on_hemisphere(normal: &Vec3) -> Vec3 {
-    let on_unit_sphere = random_unit_vector();
-    match dot(&on_unit_sphere, normal) {
-        n if n > 0.0 => on_unit_sphere, //same hemisphere as the normal
-        _ => -on_unit_sphere,
-    }
+//uniformly distributed direction on the unit sphere from a 2d sample in [0,1)^2
+pub fn sample_unit_vector(u: (f64, f64)) -> Vec3 {
+    let z = 1_f64 - 2_f64 * u.0;
+    let r = (1_f64 - z * z).max(0_f64).sqrt();
+    let phi = 2_f64 * PI * u.1;
+    Vec3::new(r * phi.cos(), r * phi.sin(), z)
 }
 
 pub fn reflect(v: &Vec3, n: &Vec3) -> Vec3 {
@@ -213,15 +190,16 @@ pub fn refract(uv: &Vec3, n: &Vec3, etai_over_etat: f64) -> Vec3 {
     r_out_perpen + r_out_parallel
 }
 
-pub fn random_in_unit_disk() -> Vec3 {
-    loop {
-        let p = Vec3::new(
-            random_f64_range(-1_f64, 1_f64),
-            random_f64_range(-1_f64, 1_f64),
-            0_f64,
-        );
-        if p.get_len_squared() < 1_f64 {
-            return p;
-        };
+//uniformly distributed point in the unit disk (z = 0) from a 2d sample in [0,1)^2, using
+//Shirley-Chiu's concentric mapping so stratified samples stay stratified on the disk
+pub fn sample_unit_disk(u: (f64, f64)) -> Vec3 {
+    let (a, b) = (2_f64 * u.0 - 1_f64, 2_f64 * u.1 - 1_f64);
+    if a == 0_f64 && b == 0_f64 {
+        return Vec3::new_empty();
     }
+    let (r, theta) = match a.abs() > b.abs() {
+        true => (a, PI / 4_f64 * (b / a)),
+        false => (b, PI / 2_f64 - PI / 4_f64 * (a / b)),
+    };
+    Vec3::new(r * theta.cos(), r * theta.sin(), 0_f64)
 }